@@ -1,27 +1,92 @@
 use crate::commands::*;
 use crate::error::{Error, Result};
+use crate::render;
+use nlbl::check::Finding;
+use nlbl::currency::ExchangeRates;
+use nlbl::expense::Alert;
+use nlbl::locale::{Locale, Notation};
+use nlbl::money::Money;
 use nlbl::*;
 
 use clap::*;
 use console::Term;
-use dialoguer::Input;
+use dialoguer::{Input, Select};
 
 const APP_TITLE: &str = "nlbt";
 const COMMAND_PROMPT: &str = ">>";
+const DUE_DAYS: i64 = 7;
+const FORECAST_DAYS: i64 = 30;
+//FORECAST_BAR_WIDTH: characters in the longest forecast chart bar
+const FORECAST_BAR_WIDTH: usize = 24;
 const COMMANDS_LIST: &str = "=============={ nos' command-line budget tool }===============\n\
                             ========{ everything in [square brackets] is a value }========\n\
                             \thelp: shows this menu, lol!\n\
                             \tincome set [amount]: sets your expected income\n\
                             \tincome raise [amount]: adds to your income\n\
+                            \tincome add [source] [amount] [frequency]: add a named income source\n\
+                            \t\t(weekly, biweekly, semi-monthly or monthly, default monthly)\n\
+                            \tincome remove [source]: remove a named income source\n\
                             \tpaid: receive your income\n\
                             \tpaid [amount]: receive some amount\n\
+                            \tpaid [source]: receive the usual amount from a source\n\
+                            \tpaid [source] [amount]: receive some amount from a source\n\
+                            \t\t(add a [memo] after an amount, or after \"memo:\", to note it in the ledger)\n\
                             \tnew [name] [amount]: create a new expenditure\n\
                             \t\t(overrides existing copies)\n\
-                            \t\t(prefix with \"*\" to make it automatic)\n\
                             \tpay [name]: pay a static expenditure\n\
                             \tpay [name] [amount]: pay some amount to an expenditure\n\
+                            \t\t(add a [memo] after an amount, or after \"memo:\", to note it in the ledger)\n\
+                            \tsplit [name] [amount] [name] [amount]...: split one payment between expenses\n\
+                            \trefund [name] [amount]: put an amount paid to an expense back into the balance\n\
+                            \treverse [id]: undo the payment with that ledger id\n\
                             \tsave [amount]: add an amount into savings\n\
                             \tsave all: add the remaining balance into savings\n\
+                            \tgoal [name] [target] [date] [share]: create or change a savings goal\n\
+                            \t\t(a [share] like 20% sets that much of each paycheck aside for it)\n\
+                            \tgoal remove [name]: remove a goal, moving its money into savings\n\
+                            \tdeposit [goal] [amount]: move balance into a goal (no amount = all)\n\
+                            \twithdraw [goal] [amount]: move money from a goal back into the balance\n\
+                            \ttransfer [goal] [goal] [amount]: move money between goals\n\
+                            \t\t(use \"savings\" as a goal for general savings)\n\
+                            \tgoals: show progress towards savings goals\n\
+                            \tdebt [name] [balance] [apr] [minimum] [compounding]: add or change a debt\n\
+                            \t\t(compounding is monthly, the default, or daily)\n\
+                            \tdebt pay [name] [amount]: pay down a debt (no amount = minimum)\n\
+                            \tdebt remove [name]: stop tracking a debt\n\
+                            \tdebts [extra]: show debts and compare payoff plans with [extra] a month\n\
+                            \tledger: list every recorded transaction\n\
+                            \tledger [name] [date] [#tag]: only those for an expense, YYYY-MM-DD and/or #tag\n\
+                            \t\t(memos can carry tags, like \"client lunch #work #reimbursable\")\n\
+                            \ttags [tag]: show totals by tag this period, or every entry with [tag]\n\
+                            \tset [name] rollover [on/off]: carry unspent money into the next period\n\
+                            \tset [name] automatic [on/off]: pay it automatically on payday\n\
+                            \tset [name] due [rule]: set when it is due, or \"none\"\n\
+                            \t\t(monthly [day], weekly [weekday/date], biweekly [weekday/date],\n\
+                            \t\tevery [n] weeks [weekday/date], yearly [MM-DD], once [date])\n\
+                            \tset [name] share [percent] [paycheck/received]: expect a percentage of income,\n\
+                            \t\tof the paycheck (default) or of income received this period, or \"none\"\n\
+                            \tset [name] warn [percent]: warn once payments reach [percent] of it, or \"none\"\n\
+                            \tset [name] limit [percent]: refuse payments past [percent] of it, or \"none\"\n\
+                            \t\t(forcing works as when the balance is too low)\n\
+                            \tmove [name] [up/down] [places]: move an expense in the order\n\
+                            \tmove [name] [top/bottom/position]: move an expense to a place in the order\n\
+                            \tsort [position/name/amount/remaining/percent]: set how expenses are sorted\n\
+                            \tdue: list bills due in the next 7 days\n\
+                            \tdue [days]: list bills due in the next [days] days\n\
+                            \tforecast [days]: chart the expected balance over the next 30 or [days] days\n\
+                            \tautopay [on/off]: turn automatic payments on payday on or off\n\
+                            \tcurrency [code]: set the account currency, like USD, EUR or JPY\n\
+                            \tlocale [name]: set how amounts are written, like en-US or de-DE\n\
+                            \tclose: archive this period and start the next\n\
+                            \tclose [period]: ...starting a period with the given name\n\
+                            \tperiods: list archived periods\n\
+                            \tperiods [period]: show an archived period\n\
+                            \tperiods [name]: compare an expense across periods\n\
+                            \tundo [steps]: undo the last command(s)\n\
+                            \tredo [steps]: redo the last undone command(s)\n\
+                            \thistory: list commands that can be undone/redone\n\
+                            \tcheck: look for inconsistencies in the account data\n\
+                            \trepair: fix everything check finds\n\
                             \tclear: clear the terminal\n\
                             \texit: close the app\n\
                             ==============================================================\n";
@@ -52,13 +117,56 @@ pub fn parse_args() -> ArgMatches {
                 .short('p')
                 .long("pay")
                 .action(ArgAction::Append)
-                .num_args(1..=2)
-                .value_names(["expense", "[amount]"])
+                .num_args(1..=3)
+                .allow_negative_numbers(true)
+                .value_names(["expense", "[amount]", "[memo]"])
                 .help("Pay an expense")
                 .long_help(
                     "Requires an expense \
                     and optionally an amount. \
-                    Without an amount, pay a given expense in full.",
+                    Without an amount, pay a given expense in full. \
+                    Any value after that, or starting with \"memo:\", is kept as a memo in the ledger.",
+                ),
+        )
+        .arg(
+            Arg::new("pay_split")
+                .long("pay-split")
+                .action(ArgAction::Append)
+                .num_args(2..)
+                .allow_negative_numbers(true)
+                .value_names(["expense", "amount"])
+                .help("Split one payment between expenses")
+                .long_help(
+                    "Pays several expenses at once, like a receipt covering groceries and household, \
+                    given as pairs of an expense and its part of the payment. \
+                    Any value after the last pair is kept as a memo in the ledger. \
+                    Nothing is paid if any of the expenses does not exist.",
+                ),
+        )
+        .arg(
+            Arg::new("refund")
+                .long("refund")
+                .action(ArgAction::Append)
+                .num_args(2..=3)
+                .value_names(["expense", "amount", "[memo]"])
+                .help("Refund part of an expense")
+                .long_help(
+                    "Puts an amount back into the balance, like a returned purchase, \
+                    and takes it off what was paid to the expense. \
+                    Refunding more than was paid needs -ff to refund only what was paid, \
+                    or -fff to go below zero.",
+                ),
+        )
+        .arg(
+            Arg::new("reverse")
+                .long("reverse")
+                .action(ArgAction::Append)
+                .num_args(1..=2)
+                .value_names(["id", "[memo]"])
+                .help("Reverse a payment")
+                .long_help(
+                    "Undoes a payment by its ledger id, as shown by --ledger, \
+                    putting it back into the balance. A payment can only be reversed once.",
                 ),
         )
         .arg(
@@ -67,12 +175,13 @@ pub fn parse_args() -> ArgMatches {
                 .long("edit")
                 .action(ArgAction::Append)
                 .num_args(2..=3)
+                .allow_negative_numbers(true)
                 .value_names(["expense", "modification"])
                 .help("Edit an existing expense")
                 .long_help(
                     "Requires at least two values: \
                     an existing expense and one or both of: \
-                    a string to rename it to \
+                    \"name:\" followed by a name to rename it to, like name:housing, \
                     and a dollar amount to change it to.",
                 ),
         )
@@ -82,6 +191,7 @@ pub fn parse_args() -> ArgMatches {
                 .long("new")
                 .action(ArgAction::Append)
                 .num_args(2)
+                .allow_negative_numbers(true)
                 .value_names(["expense", "amount"])
                 .help("Create a new expense")
                 .long_help(
@@ -94,13 +204,16 @@ pub fn parse_args() -> ArgMatches {
                 .short('P')
                 .long("paid")
                 .action(ArgAction::Append)
-                .num_args(0..=1)
-                .value_name("amount")
+                .num_args(0..=3)
+                .allow_negative_numbers(true)
+                .value_names(["source", "amount", "memo"])
                 .default_missing_value(None)
                 .help("Get paid")
                 .long_help(
                     "Get paid, either a provided amount or the fixed \
-                    income set by -C.",
+                    income set by -C. Starting with an income source name (see -I) \
+                    credits that source, by default its usual amount. \
+                    Any value after the amount, or starting with \"memo:\", is kept as a memo in the ledger.",
                 ),
         )
         .arg(
@@ -108,6 +221,7 @@ pub fn parse_args() -> ArgMatches {
                 .short('C')
                 .long("set-paycheck")
                 .num_args(1)
+                .allow_negative_numbers(true)
                 .value_name("amount")
                 .help("Set paycheck amount")
                 .long_help(
@@ -115,6 +229,175 @@ pub fn parse_args() -> ArgMatches {
                     Used whenever -P is present without a value.",
                 ),
         )
+        .arg(
+            Arg::new("income")
+                .short('I')
+                .long("income")
+                .action(ArgAction::Append)
+                .num_args(2..=3)
+                .allow_negative_numbers(true)
+                .value_names(["source", "amount", "frequency"])
+                .help("Add or change an income source")
+                .long_help(
+                    "Sets a named income source, paid the given amount weekly, biweekly, \
+                    semi-monthly or monthly (the default). Sources are credited with \
+                    -P <source>, and make up the expected monthly income.",
+                ),
+        )
+        .arg(
+            Arg::new("remove_income")
+                .long("remove-income")
+                .action(ArgAction::Append)
+                .num_args(1)
+                .value_name("source")
+                .help("Remove an income source"),
+        )
+        .arg(
+            Arg::new("goal")
+                .short('g')
+                .long("goal")
+                .action(ArgAction::Append)
+                .num_args(1..=4)
+                .allow_negative_numbers(true)
+                .value_names(["goal", "target", "deadline", "share"])
+                .help("Create or change a savings goal")
+                .long_help(
+                    "Creates a named savings goal, or changes an existing one, \
+                    with an optional target amount, YYYY-MM-DD deadline, \
+                    and share of every paycheck to set aside for it, like 20%.",
+                ),
+        )
+        .arg(
+            Arg::new("remove_goal")
+                .long("remove-goal")
+                .action(ArgAction::Append)
+                .num_args(1)
+                .value_name("goal")
+                .help("Remove a savings goal")
+                .long_help("Removes a savings goal, moving whatever it held into general savings."),
+        )
+        .arg(
+            Arg::new("deposit")
+                .short('d')
+                .long("deposit")
+                .action(ArgAction::Append)
+                .num_args(1..=3)
+                .allow_negative_numbers(true)
+                .value_names(["goal", "[amount]", "[memo]"])
+                .help("Deposit into savings")
+                .long_help(
+                    "Moves an amount, or the whole remaining balance, into a savings goal. \
+                    Use \"savings\" as the goal for general savings.",
+                ),
+        )
+        .arg(
+            Arg::new("withdraw")
+                .short('w')
+                .long("withdraw")
+                .action(ArgAction::Append)
+                .num_args(2..=3)
+                .allow_negative_numbers(true)
+                .value_names(["goal", "amount", "[memo]"])
+                .help("Withdraw from savings")
+                .long_help(
+                    "Moves an amount out of a savings goal back into the balance. \
+                    Use \"savings\" as the goal for general savings.",
+                ),
+        )
+        .arg(
+            Arg::new("transfer")
+                .short('t')
+                .long("transfer")
+                .action(ArgAction::Append)
+                .num_args(3)
+                .allow_negative_numbers(true)
+                .value_names(["from", "to", "amount"])
+                .help("Move savings between goals")
+                .long_help(
+                    "Moves an amount from one savings goal to another. \
+                    Use \"savings\" as a goal for general savings.",
+                ),
+        )
+        .arg(
+            Arg::new("debt")
+                .long("debt")
+                .action(ArgAction::Append)
+                .num_args(4..=5)
+                .value_names(["debt", "balance", "apr", "minimum", "[compounding]"])
+                .help("Add or change a debt")
+                .long_help(
+                    "Tracks a loan or card balance owed, with its APR, like 19.99%, \
+                    its monthly minimum payment, and whether interest compounds \
+                    monthly (the default) or daily.",
+                ),
+        )
+        .arg(
+            Arg::new("remove_debt")
+                .long("remove-debt")
+                .action(ArgAction::Append)
+                .num_args(1)
+                .value_name("debt")
+                .help("Stop tracking a debt"),
+        )
+        .arg(
+            Arg::new("pay_debt")
+                .long("pay-debt")
+                .action(ArgAction::Append)
+                .num_args(1..=3)
+                .value_names(["debt", "[amount]", "[memo]"])
+                .help("Pay down a debt")
+                .long_help(
+                    "Pays an amount, or the minimum payment, towards a debt, \
+                    after adding the interest it is owed so far.",
+                ),
+        )
+        .arg(
+            Arg::new("debts")
+                .long("debts")
+                .num_args(0..=1)
+                .value_name("extra")
+                .help("Show debts and payoff plans")
+                .long_help(
+                    "Lists every debt, and compares paying them off smallest balance first \
+                    (snowball) with highest APR first (avalanche), putting the given extra \
+                    amount towards them every month on top of the minimums.",
+                ),
+        )
+        .arg(
+            Arg::new("goals")
+                .long("goals")
+                .action(ArgAction::SetTrue)
+                .help("Show savings goal progress")
+                .long_help(
+                    "Shows every savings goal, how close it is to its target, and how much \
+                    to set aside per paycheck to reach it by its deadline.",
+                ),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("Check the account data")
+                .long_help(
+                    "Looks for inconsistencies in the account data: expenses missing their \
+                    expected or paid amount, names that differ only in case, negative expected \
+                    amounts, and a balance that does not match the ledger.",
+                ),
+        )
+        .arg(
+            Arg::new("repair")
+                .long("repair")
+                .requires("check")
+                .action(ArgAction::SetTrue)
+                .help("Fix what --check finds")
+                .long_help(
+                    "Fixes everything --check finds after any --undo or --redo, \
+                    and before running any other command. \
+                    Duplicate names are merged, missing amounts and negative expected amounts \
+                    become zero, and a balance the ledger does not explain is kept, with an \
+                    adjustment recorded in the ledger. Can be undone.",
+                ),
+        )
         .arg(
             Arg::new("clear")
                 .short('c')
@@ -125,7 +408,183 @@ pub fn parse_args() -> ArgMatches {
                 .long_help(
                     "Resets amounts paid to expenses to zero. \
                     If given without any names, resets all, otherwise only clears \
-                    provided expense names. Supports \"!<name>\" for inverted filtering.",
+                    provided expense names. Given every name as \"!<name>\", clears all but those.",
+                ),
+        )
+        .arg(
+            Arg::new("set")
+                .short('s')
+                .long("set")
+                .action(ArgAction::Append)
+                .num_args(2..=6)
+                .value_names(["expense", "option", "value"])
+                .help("Change an expense setting")
+                .long_help(
+                    "Changes a setting of an existing expense. \
+                    Options: \"rollover [on/off]\" carries unspent money \
+                    into the next period, \"automatic [on/off]\" pays what is left \
+                    of it on payday (see -a), \"due <rule>\" sets when it is due, \
+                    where the rule is one of: monthly <day>, weekly <weekday|date>, \
+                    biweekly <weekday|date>, every <n> weeks <weekday|date>, \
+                    yearly <MM-DD>, once <YYYY-MM-DD> or none, \
+                    \"share <percent> [paycheck|received]\" expects a percentage of the paycheck, \
+                    or of income received this period, recomputed whenever either changes, \
+                    or none to keep the current amount fixed. Editing the amount also makes it fixed. \
+                    \"warn <percent>\" warns when a payment brings it to that much of its expected \
+                    amount, \"limit <percent>\" refuses payments past that much unless forced, \
+                    like a payment the balance cannot cover, and either can be none.",
+                ),
+        )
+        .arg(
+            Arg::new("move")
+                .long("move")
+                .action(ArgAction::Append)
+                .num_args(2..=3)
+                .value_names(["expense", "position"])
+                .help("Move an expense in the order")
+                .long_help(
+                    "Moves an expense \"up\" or \"down\", optionally by a number of places, \
+                    to the \"top\" or \"bottom\", or to a position counted from 1. \
+                    Expenses are kept in the order they were added unless moved.",
+                ),
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .num_args(1)
+                .value_name("by")
+                .help("Set how expenses are sorted")
+                .long_help(
+                    "Sets how expenses are sorted when shown: by \"position\" in the \
+                    budget's own order (the default), \"name\", expected \"amount\", \
+                    \"remaining\" left to pay or \"percent\" used.",
+                ),
+        )
+        .arg(
+            Arg::new("currency")
+                .long("currency")
+                .num_args(1)
+                .value_name("code")
+                .help("Set the account currency")
+                .long_help(
+                    "Sets the currency of the account by its ISO 4217 code, such as USD, \
+                    EUR, JPY or KWD. Amounts are shown and entered with its symbol and \
+                    decimal places. Once amounts are recorded, it can only change to a \
+                    currency with the same number of decimal places.",
+                ),
+        )
+        .arg(
+            Arg::new("locale")
+                .long("locale")
+                .num_args(1)
+                .value_name("name")
+                .help("Set how amounts are written")
+                .long_help(format!(
+                    "Sets how the account writes and reads amounts: digit grouping, decimal \
+                    mark, where the currency symbol goes and how negatives look. \
+                    Supported: {}",
+                    Locale::names().collect::<Vec<_>>().join(", ")
+                )),
+        )
+        .arg(
+            Arg::new("consolidate")
+                .long("consolidate")
+                .num_args(0..=1)
+                .value_name("currency")
+                .help("Show every account in one currency")
+                .long_help(
+                    "Totals every account, converted into the given currency, or the \
+                    currency of the loaded account. Rates come from data/rates.json, \
+                    written like {\"base\": \"USD\", \"rates\": {\"EUR\": \"0.92\", \"JPY\": \"151.3\"}}, \
+                    meaning one unit of the base currency buys that much of each.",
+                ),
+        )
+        .arg(
+            Arg::new("auto_pay")
+                .short('a')
+                .long("auto-pay")
+                .num_args(1)
+                .value_name("on|off")
+                .help("Toggle automatic payments")
+                .long_help(
+                    "Opt the account in or out of automatic payments. When on, \
+                    getting paid also pays what is left of every automatic expense, \
+                    in the budget's order, failing if the paycheck does not cover them.",
+                ),
+        )
+        .arg(
+            Arg::new("close_period")
+                .short('E')
+                .long("close-period")
+                .num_args(0..=1)
+                .value_name("next")
+                .help("Close the current budget period")
+                .long_help(
+                    "Archives expected vs. actual for every expense, the balance \
+                    and savings, then starts a new period with nothing paid. \
+                    The new period is named after the current month, unless a name is given.",
+                ),
+        )
+        .arg(
+            Arg::new("periods")
+                .long("periods")
+                .num_args(0..=1)
+                .value_name("period|expense")
+                .help("Show archived budget periods")
+                .long_help(
+                    "Lists every closed period. Given a period name, shows that period, \
+                    given an expense name, compares it across periods.",
+                ),
+        )
+        .arg(
+            Arg::new("due")
+                .long("due")
+                .num_args(0..=1)
+                .value_name("days")
+                .value_parser(value_parser!(i64))
+                .help("Show bills coming due")
+                .long_help(
+                    "Lists expenses due in the next 7 days, or the given number of days, \
+                    up to 3660, and whether what was paid this period already covers them.",
+                ),
+        )
+        .arg(
+            Arg::new("forecast")
+                .long("forecast")
+                .num_args(0..=1)
+                .value_name("days")
+                .value_parser(value_parser!(i64))
+                .help("Forecast the balance")
+                .long_help(
+                    "Charts the expected balance over the next 30 days, or the given number of \
+                    days up to 3660, from paydays of every income source, due dates of scheduled bills and \
+                    what is left of other expenses, which come due again every month. \
+                    Shows the first day the balance would go negative.",
+                ),
+        )
+        .arg(
+            Arg::new("undo")
+                .short('u')
+                .long("undo")
+                .num_args(0..=1)
+                .value_name("steps")
+                .help("Undo previous command(s)")
+                .long_help(
+                    "Undoes the last applied command, or the given number of them. \
+                    History is kept in the account file, so this works across runs. \
+                    Applied before any other command.",
+                ),
+        )
+        .arg(
+            Arg::new("redo")
+                .short('R')
+                .long("redo")
+                .num_args(0..=1)
+                .value_name("steps")
+                .help("Redo undone command(s)")
+                .long_help(
+                    "Re-applies the last undone command, or the given number of them. \
+                    Applying any new command clears what can be redone.",
                 ),
         )
         .arg(
@@ -135,9 +594,10 @@ pub fn parse_args() -> ArgMatches {
                 .action(ArgAction::Count)
                 .help("Force payments")
                 .long_help(
-                    "If present, skips the confirmation dialogue. Use twice to skip the \
-                    \"Cannot afford\" confirmation message and use remamining balance \
-                    for payments. Use thrice to enable overdrafting.",
+                    "Without it, a payment larger than your balance asks what to do. If present, \
+                    skips the confirmation dialogue and fails with \"Cannot afford\". Use twice \
+                    to use the remaining balance for payments instead. Use thrice to enable \
+                    overdrafting.",
                 ),
         )
         .arg(
@@ -160,6 +620,34 @@ pub fn parse_args() -> ArgMatches {
                 .help("Create a new account")
                 .long_help("Creates and selects a new account with the given name."),
         )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .requires("new_account")
+                .num_args(1)
+                .value_name("template")
+                .help("Create from a template")
+                .long_help(format!(
+                    "Sets up the new account with the expenses, income and savings goals \
+                    of a template saved with --save-template, or a built in one: {}.",
+                    nlbl::template::Template::builtin_names()
+                        .map(|(name, description)| format!("\"{name}\" ({description})"))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )),
+        )
+        .arg(
+            Arg::new("save_template")
+                .long("save-template")
+                .num_args(1)
+                .value_name("template")
+                .help("Save as a template")
+                .long_help(
+                    "Saves the expenses, income and savings goals of the account, \
+                    without any of its money, as a template for --template to create \
+                    new accounts from.",
+                ),
+        )
         .arg(
             Arg::new("dry_run")
                 .short('X')
@@ -214,6 +702,31 @@ pub fn parse_args() -> ArgMatches {
                     modifications made.",
                 ),
         )
+        .arg(
+            Arg::new("ledger")
+                .short('L')
+                .long("ledger")
+                .num_args(0..=3)
+                .value_names(["expense", "date", "#tag"])
+                .help("Show the transaction ledger")
+                .long_help(
+                    "Lists every recorded income, payment, savings deposit, \
+                    clear and edit. Optionally filtered to an expense, \
+                    a YYYY-MM-DD date and/or a #tag from memos.",
+                ),
+        )
+        .arg(
+            Arg::new("tags")
+                .long("tags")
+                .num_args(0..=1)
+                .value_name("tag")
+                .help("Show totals by tag")
+                .long_help(
+                    "Memos can carry tags, like \"client lunch #work #reimbursable\". \
+                    Shows what was spent and received under each tag this period, \
+                    or every entry with the given tag.",
+                ),
+        )
         .arg(
             Arg::new("json")
                 .short('j')
@@ -238,6 +751,45 @@ pub fn parse_settings(args: clap::ArgMatches) -> Result<AppConfig> {
     let verbosity: u8 =
         1 + args.get_count("verbose") - (interactive_mode || args.get_flag("quiet")) as u8;
     let json: bool = args.get_flag("json");
+    let ledger: Option<Vec<String>> = args.contains_id("ledger").then(|| {
+        args.get_many::<String>("ledger")
+            .map(|vals| vals.cloned().collect())
+            .unwrap_or_default()
+    });
+
+    let periods: Option<Vec<String>> = args.contains_id("periods").then(|| {
+        args.get_many::<String>("periods")
+            .map(|vals| vals.cloned().collect())
+            .unwrap_or_default()
+    });
+
+    let due: Option<i64> = args
+        .contains_id("due")
+        .then(|| args.get_one::<i64>("due").copied().unwrap_or(DUE_DAYS));
+
+    let forecast: Option<i64> = args.contains_id("forecast").then(|| {
+        args.get_one::<i64>("forecast")
+            .copied()
+            .unwrap_or(FORECAST_DAYS)
+    });
+
+    let goals: bool = args.get_flag("goals");
+
+    let check: Option<bool> = args.get_flag("check").then(|| args.get_flag("repair"));
+
+    let save_template: Option<String> = args.get_one("save_template").cloned();
+
+    let tags: Option<Option<String>> = args
+        .contains_id("tags")
+        .then(|| args.get_one::<String>("tags").cloned());
+
+    let debts: Option<Option<String>> = args
+        .contains_id("debts")
+        .then(|| args.get_one::<String>("debts").cloned());
+
+    let consolidate: Option<Option<String>> = args
+        .contains_id("consolidate")
+        .then(|| args.get_one::<String>("consolidate").cloned());
 
     let app_settings = AppSettings {
         mem_only,
@@ -246,6 +798,16 @@ pub fn parse_settings(args: clap::ArgMatches) -> Result<AppConfig> {
         force,
         verbosity,
         json,
+        ledger,
+        periods,
+        due,
+        goals,
+        check,
+        tags,
+        save_template,
+        debts,
+        forecast,
+        consolidate,
     };
 
     //account settings/commands
@@ -254,126 +816,741 @@ pub fn parse_settings(args: clap::ArgMatches) -> Result<AppConfig> {
         .or(args.get_one("new_account"))
         .cloned();
     let create: bool = args.contains_id("new_account");
+    let template: Option<String> = args.get_one("template").cloned();
     let default_rename: Option<String> = args.get_one("default_name").cloned();
 
     let account_options = AccountOptions {
         account,
         create,
+        template,
         default_rename,
     };
 
-    //parse commands from args with this order ->
-    let budget_commands: BudgetCommands = ["paycheck", "paid", "clear", "edit", "new", "pay"]
-        .iter()
-        //do not process commands that arent present in args
-        .filter(|id| args.contains_id(id))
-        //for each command...
-        .flat_map(|id| {
-            //get every time the command shows up in args...
-            //safely unwrap thanks to filter()
-            args.get_occurrences(id).unwrap().map(move |occ| {
-                //and turn them into BudgetCommands
-                command_from_arg(id, occ).map_err(|e| {
-                    println!("{e}");
-                    e
-                })
-            })
-        })
-        .collect::<Result<Vec<_>>>()?;
+    //collect commands from args with this order ->
+    let command_args: Vec<(String, Vec<String>)> = [
+        "undo",
+        "redo",
+        "currency",
+        "locale",
+        "close_period",
+        "auto_pay",
+        "paycheck",
+        "income",
+        "remove_income",
+        "paid",
+        "clear",
+        "edit",
+        "new",
+        "set",
+        "move",
+        "sort",
+        "pay",
+        "pay_split",
+        "refund",
+        "reverse",
+        "goal",
+        "remove_goal",
+        "deposit",
+        "withdraw",
+        "transfer",
+        "debt",
+        "remove_debt",
+        "pay_debt",
+    ]
+    .iter()
+    //do not process commands that arent present in args
+    .filter(|id| args.contains_id(id))
+    //for each command...
+    .flat_map(|id| {
+        //get every time the command shows up in args...
+        //safely unwrap thanks to filter()
+        args.get_occurrences::<String>(id)
+            .unwrap()
+            //and keep its values, to be turned into BudgetCommands once the account is loaded
+            .map(move |occ| (id.to_string(), occ.cloned().collect()))
+    })
+    .collect();
+    //repairs go right after undo and redo, so they fix the state those restore,
+    //and every other command works on consistent data
+    let mut command_args = command_args;
+    if check == Some(true) {
+        let at = command_args
+            .iter()
+            .take_while(|(id, _)| id == "undo" || id == "redo")
+            .count();
+        command_args.insert(at, (String::from("repair"), Vec::new()));
+    }
 
     #[cfg(debug_assertions)]
     {
         println!("[DEV] parsed app settings: {app_settings:?}");
         println!("[DEV] parsed account options: {account_options:?}");
-        println!("[DEV] parsed budget commands: {budget_commands:?}");
     }
 
     Ok(AppConfig {
         app_settings,
         account_options,
-        budget_commands,
+        command_args,
     })
 }
 
-pub fn parse_command(input: &str) -> Result<BudgetCommand> {
+//words(): splits input on whitespace, keeping a currency symbol or code written apart from its amount attached to it,
+//so amounts can be pasted back the way they are shown, like "1.234,56 €" or "-R$ 5,00"
+fn words(input: &str, notation: Notation) -> Vec<&str> {
+    let c = notation.currency;
+    let is_symbol = |w: &str| {
+        let w = w.trim_start_matches(['-', '+', '(']);
+        w.eq_ignore_ascii_case(c.symbol()) || w.eq_ignore_ascii_case(c.code())
+    };
+    let mut words: Vec<&str> = Vec::new();
+    //start of a symbol still waiting for its amount
+    let mut pending: Option<usize> = None;
+    for word in input.split_whitespace() {
+        //words are slices of input, so their offsets give the text spanning several of them
+        let start = word.as_ptr() as usize - input.as_ptr() as usize;
+        let end = start + word.len();
+        if is_symbol(word) && pending.is_none() {
+            match words.last_mut() {
+                Some(prev) if notation.parse(prev).is_ok() => {
+                    let prev_start = prev.as_ptr() as usize - input.as_ptr() as usize;
+                    *prev = &input[prev_start..end];
+                }
+                _ => pending = Some(start),
+            }
+            continue;
+        }
+        words.push(match pending.take() {
+            Some(symbol_start) => &input[symbol_start..end],
+            None => word,
+        });
+    }
+    if let Some(symbol_start) = pending {
+        words.push(input[symbol_start..].trim_end());
+    }
+    words
+}
+
+pub fn parse_command(input: &str, notation: Notation) -> Result<BudgetCommand> {
     use BudgetCommand as BC;
 
-    let command = input.split_whitespace();
-    let command: Vec<&str> = command.collect();
+    let command: Vec<&str> = words(input, notation);
 
     let cmd = match *command.first().unwrap_or(&"") {
-        "help" => {
-            println!("{COMMANDS_LIST}");
-            BC::Nothing
-        }
         "income" => match *command.get(1).unwrap_or(&"") {
             "set" => {
                 let amount = *command.get(2).ok_or(Error::InvalidCommand("new".into()))?;
-                let amount = util::parse_dollar_string(amount)?;
+                let amount = notation.parse(amount)?;
                 BC::SetPaycheck { amount }
             }
             "raise" => {
-                todo!("raise income command!");
-                let amount = *command.get(2).ok_or(Error::InvalidCommand("new".into()))?;
-                let amount = util::parse_dollar_string(amount)?;
-                BC::SetPaycheck { amount }
+                let amount = *command
+                    .get(2)
+                    .ok_or(Error::InvalidCommand("raise".into()))?;
+                let amount = notation.parse(amount)?;
+                BC::RaisePaycheck { amount }
+            }
+            "add" => {
+                let name = *command.get(2).ok_or(Error::InvalidCommand("add".into()))?;
+                let amount = *command.get(3).ok_or(Error::InvalidCommand("add".into()))?;
+                BC::SetIncomeSource {
+                    name: name.into(),
+                    amount: notation.parse(amount)?,
+                    frequency: match command.get(4) {
+                        Some(v) => v.parse()?,
+                        None => income::Frequency::Monthly,
+                    },
+                }
             }
+            "remove" => BC::RemoveIncomeSource {
+                name: String::from(
+                    *command
+                        .get(2)
+                        .ok_or(Error::InvalidCommand("remove".into()))?,
+                ),
+            },
             other => Err(Error::InvalidCommand(other.into()))?,
         },
-        "paid" => match *command.get(1).unwrap_or(&"") {
-            "" => BC::Paid { amount: None },
-            val => {
-                let amount = util::parse_dollar_string(val)?;
-                BC::Paid {
-                    amount: Some(amount),
-                }
+        "set" => {
+            let name = String::from(*command.get(1).ok_or(Error::InvalidCommand("set".into()))?);
+            let option = *command.get(2).ok_or(Error::InvalidCommand("set".into()))?;
+            BC::SetExpenseOption {
+                name,
+                option: expense::ExpenseOption::from_args(option, &command[3..])?,
             }
+        }
+        "move" => {
+            let name = String::from(*command.get(1).ok_or(Error::InvalidCommand("move".into()))?);
+            BC::MoveExpense {
+                name,
+                position: expense::Position::from_args(&command[2..])?,
+            }
+        }
+        "sort" => BC::SetExpenseSort {
+            sort: command[1..].join(" ").parse()?,
+        },
+        "currency" => BC::SetCurrency {
+            currency: command
+                .get(1)
+                .ok_or(Error::InvalidCommand("currency".into()))?
+                .parse()?,
+        },
+        "locale" => BC::SetLocale {
+            locale: command
+                .get(1)
+                .ok_or(Error::InvalidCommand("locale".into()))?
+                .parse()?,
+        },
+        "autopay" => BC::SetAutomaticPayments {
+            enabled: expense::parse_toggle(command.get(1).copied())?,
         },
+        "close" => BC::ClosePeriod {
+            next: command.get(1).map(|s| s.to_string()),
+        },
+        "undo" => BC::Undo {
+            steps: steps_from_arg(command.get(1))?,
+        },
+        "redo" => BC::Redo {
+            steps: steps_from_arg(command.get(1))?,
+        },
+        "repair" => BC::Repair,
+        "paid" => {
+            let (source, amount, memo) = source_amount_and_memo(command.iter().skip(1), notation)?;
+            BC::Paid {
+                amount,
+                memo,
+                source,
+            }
+        }
         "new" => {
             let name = String::from(*command.get(1).ok_or(Error::InvalidCommand("new".into()))?);
             let amount = *command.get(2).ok_or(Error::InvalidCommand("new".into()))?;
-            let amount = util::parse_dollar_string(amount)?;
+            let amount = notation.parse(amount)?;
             BC::NewExpense { name, amount }
         }
         "pay" => {
             let name = String::from(*command.get(1).ok_or(Error::InvalidCommand("pay".into()))?);
-            match *command.get(2).unwrap_or(&"") {
-                "" => BC::PayExpense { name, amount: None },
-                val => {
-                    let amount = util::parse_dollar_string(val)?;
-                    BC::PayExpense {
-                        name,
-                        amount: Some(amount),
-                    }
+            let (amount, memo) = amount_and_memo(command.iter().skip(2), notation)?;
+            BC::PayExpense { name, amount, memo }
+        }
+        "split" => split_from_args(command.iter().skip(1), notation)?,
+        "refund" => {
+            let name = String::from(
+                *command
+                    .get(1)
+                    .ok_or(Error::InvalidCommand("refund".into()))?,
+            );
+            let amount = *command
+                .get(2)
+                .ok_or(Error::InvalidCommand("refund".into()))?;
+            BC::Refund {
+                name,
+                amount: notation.parse(amount)?,
+                memo: memo_from_args(command.iter().skip(3)),
+            }
+        }
+        "reverse" => BC::ReversePayment {
+            id: id_from_arg(
+                command
+                    .get(1)
+                    .ok_or(Error::InvalidCommand("reverse".into()))?,
+            )?,
+            memo: memo_from_args(command.iter().skip(2)),
+        },
+        "save" => match *command.get(1).unwrap_or(&"") {
+            "" => Err(Error::InvalidCommand("empty save amount".into()))?,
+            "all" => BC::Savings {
+                amount: None,
+                memo: None,
+            },
+            val => {
+                let (amount, memo) = amount_and_memo(command.iter().skip(1), notation)?;
+                BC::Savings {
+                    amount: Some(amount.ok_or(Error::InvalidCommand(val.into()))?),
+                    memo,
                 }
             }
+        },
+        "goal" => match *command.get(1).ok_or(Error::InvalidCommand("goal".into()))? {
+            "remove" => BC::RemoveSavingsGoal {
+                name: String::from(*command.get(2).ok_or(Error::InvalidCommand("goal".into()))?),
+            },
+            name => {
+                let (target, deadline, share) = goal_from_args(command.iter().skip(2), notation)?;
+                BC::SetSavingsGoal {
+                    name: name.into(),
+                    target,
+                    deadline,
+                    share,
+                }
+            }
+        },
+        "deposit" => {
+            let goal = *command
+                .get(1)
+                .ok_or(Error::InvalidCommand("deposit".into()))?;
+            let (amount, memo) = amount_and_memo(command.iter().skip(2), notation)?;
+            BC::Deposit {
+                goal: bucket_from_arg(goal),
+                amount,
+                memo,
+            }
         }
-        "save" => {
-            todo!("savings!");
-            // match command[1] {
-            // "" => Err(Error::InvalidCommand("empty save amount".into()))?,
-            // "all" => bud.save_all(),
-            // _ => {
-            //     let amount = util::parse_dollar_string(command[1])?;
-            //     bud.save(amount)
-            // }
+        "withdraw" => {
+            let goal = *command
+                .get(1)
+                .ok_or(Error::InvalidCommand("withdraw".into()))?;
+            let (amount, memo) = amount_and_memo(command.iter().skip(2), notation)?;
+            BC::Withdraw {
+                goal: bucket_from_arg(goal),
+                amount: amount.ok_or(Error::InvalidCommand("withdraw".into()))?,
+                memo,
+            }
         }
-        // "clear" => match term.clear_screen() {
-        //     Ok(()) => Ok(String::new()),
-        //     Err(e) => Err(Error::IoFailure(e)),
-        // },
+        "debt" => match *command.get(1).ok_or(Error::InvalidCommand("debt".into()))? {
+            "remove" => BC::RemoveDebt {
+                name: String::from(*command.get(2).ok_or(Error::InvalidCommand("debt".into()))?),
+            },
+            "pay" => {
+                let name = *command.get(2).ok_or(Error::InvalidCommand("debt".into()))?;
+                let (amount, memo) = amount_and_memo(command.iter().skip(3), notation)?;
+                BC::PayDebt {
+                    name: name.into(),
+                    amount,
+                    memo,
+                }
+            }
+            name => debt_from_args(name, command.iter().skip(2), notation)?,
+        },
+        "transfer" => {
+            let from = *command
+                .get(1)
+                .ok_or(Error::InvalidCommand("transfer".into()))?;
+            let to = *command
+                .get(2)
+                .ok_or(Error::InvalidCommand("transfer".into()))?;
+            let amount = *command
+                .get(3)
+                .ok_or(Error::InvalidCommand("transfer".into()))?;
+            BC::Transfer {
+                from: bucket_from_arg(from),
+                to: bucket_from_arg(to),
+                amount: notation.parse(amount)?,
+            }
+        }
+        //the interactive screen is cleared and redrawn after every command, so clear does nothing else
+        "clear" => BC::Nothing,
         cmd => return Err(Error::InvalidCommand(cmd.into()))?,
     };
 
     Ok(cmd)
 }
 
-pub fn run_interactive(mut bud: Budget) -> Result<Budget> {
+//parse_view(): handles interactive commands that only display something, returning the text to show
+pub fn parse_view(input: &str, bud: &Budget) -> Option<Result<String>> {
+    let command: Vec<&str> = input.split_whitespace().collect();
+
+    match *command.first().unwrap_or(&"") {
+        "help" => Some(Ok(COMMANDS_LIST.into())),
+        "ledger" => Some(ledger_view(bud, &command[1..])),
+        "history" => Some(Ok(history_view(bud))),
+        "periods" => Some(Ok(periods_view(bud, command.get(1).copied()))),
+        "goals" => Some(Ok(goals_view(bud))),
+        "check" => Some(Ok(check_view(&bud.check(), bud.notation(), false))),
+        "tags" => Some(Ok(tags_view(bud, command.get(1).copied()))),
+        "debts" => Some(match command.get(1) {
+            Some(extra) => bud
+                .notation()
+                .parse(extra)
+                .map_err(Error::from)
+                .map(|extra| debts_view(bud, extra)),
+            None => Ok(debts_view(bud, Money::ZERO)),
+        }),
+        "due" => Some(match command.get(1) {
+            Some(days) => days
+                .parse()
+                .map(|days| due_view(bud, days))
+                .map_err(|_| Error::InvalidCommand((*days).into())),
+            None => Ok(due_view(bud, DUE_DAYS)),
+        }),
+        "forecast" => Some(match command.get(1) {
+            Some(days) => days
+                .parse()
+                .map(|days| forecast_view(bud, days))
+                .map_err(|_| Error::InvalidCommand((*days).into())),
+            None => Ok(forecast_view(bud, FORECAST_DAYS)),
+        }),
+        _ => None,
+    }
+}
+
+//periods_view(): lists archived periods, shows one of them, or compares an expense across them
+pub fn periods_view(bud: &Budget, filter: Option<&str>) -> String {
+    if let Some(period) = filter.and_then(|name| bud.archived_period(name)) {
+        return bud.notation().show(period).to_string();
+    }
+
+    let periods = bud.archived_periods();
+    if periods.is_empty() {
+        return String::from("No archived periods.");
+    }
+
+    let mut out = Vec::new();
+    match filter.map(|s| s.to_ascii_lowercase()) {
+        Some(expense) => {
+            out.push(format!("{}:", util::to_title_case(expense.clone())));
+            for period in periods {
+                let c = Notation::new(period.currency, bud.locale());
+                out.push(match period.expense(&expense) {
+                    Some(exp) => format!(
+                        "\t{}: {}/{}",
+                        period.name,
+                        c.format(exp.actual),
+                        c.format(exp.expected)
+                    ),
+                    None => format!("\t{}: -", period.name),
+                });
+            }
+        }
+        None => {
+            for period in periods {
+                let c = Notation::new(period.currency, bud.locale());
+                out.push(format!(
+                    "{} ({} to {}): spent {}/{}, income {}, balance {}, savings {}",
+                    period.name,
+                    period.started,
+                    period.closed,
+                    c.format(period.total_actual()),
+                    c.format(period.total_expected()),
+                    c.format(period.income),
+                    c.format(period.balance),
+                    c.format(period.savings)
+                ));
+            }
+        }
+    }
+    out.join("\n")
+}
+
+//alerts_view(): one warning per threshold payments went past
+pub fn alerts_view<'a>(alerts: impl IntoIterator<Item = &'a Alert>, notation: Notation) -> String {
+    alerts
+        .into_iter()
+        .map(|a| format!("warning: {}", notation.show(a)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+//outcomes_view(): what each command did, with its warnings, leaving out commands that did nothing
+pub fn outcomes_view(outcomes: &[Outcome], notation: Notation) -> String {
+    outcomes
+        .iter()
+        .filter(|o| o.status != Status::Skipped)
+        .flat_map(|o| {
+            let warnings = alerts_view(&o.warnings, notation);
+            [o.message.clone()]
+                .into_iter()
+                .chain((!warnings.is_empty()).then_some(warnings))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+//check_view(): lists what Budget::check() found, or what Budget::repair() fixed
+pub fn check_view(findings: &[Finding], notation: Notation, repaired: bool) -> String {
+    if findings.is_empty() {
+        return String::from("No problems found.");
+    }
+    let mut out = vec![match repaired {
+        true => format!("Repaired {} problem(s):", findings.len()),
+        false => format!("Found {} problem(s):", findings.len()),
+    }];
+    out.extend(findings.iter().map(|f| format!("\t{}", notation.show(f))));
+    out.join("\n")
+}
+
+//goals_view(): shows general savings and progress towards every savings goal
+pub fn goals_view(bud: &Budget) -> String {
+    let c = bud.notation();
+    let mut out = vec![format!(
+        "Savings: {} ({} in general savings)",
+        c.format(bud.total_savings()),
+        c.format(bud.savings())
+    )];
+    let goals = bud.savings_goals();
+    if goals.is_empty() {
+        out.push(String::from("No savings goals."));
+    }
+    out.extend(goals.iter().map(|g| format!("\t{g}")));
+    out.join("\n")
+}
+
+//forecast_view(): charts the expected balance on every day something is due or paid over the next `days` days,
+//and when it first goes negative
+pub fn forecast_view(bud: &Budget, days: i64) -> String {
+    let forecast = bud.forecast(days);
+    let days = days.min(forecast::MAX_FORECAST_DAYS);
+    let c = forecast.notation;
+    let mut out = vec![format!(
+        "Forecast for the next {days} days, from {}:",
+        c.format(forecast.starting_balance)
+    )];
+
+    let widest = forecast
+        .event_days()
+        .map(|d| d.balance.cents().unsigned_abs())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for day in forecast.event_days() {
+        let len = (day.balance.cents().unsigned_abs() as u128 * FORECAST_BAR_WIDTH as u128
+            / widest as u128) as usize;
+        let bar = match day.balance.is_negative() {
+            true => "░".repeat(len),
+            false => "█".repeat(len),
+        };
+        let events: Vec<String> = day
+            .events
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} {}",
+                    util::to_title_case(e.name.clone()),
+                    c.format_change(e.amount)
+                )
+            })
+            .collect();
+        out.push(format!(
+            "{}  {bar:<width$}  {:>12}  {}",
+            day.date,
+            c.format(day.balance),
+            events.join(", "),
+            width = FORECAST_BAR_WIDTH
+        ));
+    }
+    if forecast.event_days().next().is_none() {
+        out.push(String::from("Nothing expected."));
+    }
+
+    out.push(match (forecast.first_negative(), forecast.lowest()) {
+        (Some(day), _) => format!(
+            "Short on {}: balance goes to {}",
+            day.date,
+            c.format(day.balance)
+        ),
+        (None, Some(day)) => format!(
+            "Stays covered, lowest {} on {}",
+            c.format(day.balance),
+            day.date
+        ),
+        (None, None) => String::new(),
+    });
+    out.join("\n")
+}
+
+//debts_view(): lists every debt, and how snowball and avalanche payoff compare with the given extra each month
+pub fn debts_view(bud: &Budget, extra: Money) -> String {
+    let debts = bud.debts();
+    if debts.is_empty() {
+        return String::from("No debts.");
+    }
+    let c = bud.notation();
+    let mut out = vec![format!("Debts: {}", c.format(bud.total_debt()))];
+    out.extend(
+        debts.iter().map(|(name, debt)| {
+            format!("\t{}: {}", util::to_title_case(name.clone()), c.show(debt))
+        }),
+    );
+    out.push(format!("Paying {} extra a month:", c.format(extra)));
+    out.extend(
+        bud.payoff_plans(extra)
+            .iter()
+            .map(|plan| format!("\t{plan}")),
+    );
+    out.join("\n")
+}
+
+//due_view(): lists bills due in the next `days` days
+pub fn due_view(bud: &Budget, days: i64) -> String {
+    let bills = bud.due_within(days);
+    if bills.is_empty() {
+        return format!("Nothing due in the next {days} days.");
+    }
+    let mut out = vec![format!("Due in the next {days} days:")];
+    out.extend(bills.iter().map(|b| b.to_string()));
+    out.join("\n")
+}
+
+//consolidated_view(): every account's balance and savings in its own notation,
+//with totals converted into the home currency
+pub fn consolidated_view(
+    budgets: &[Budget],
+    home: Notation,
+    rates: &ExchangeRates,
+) -> nlbl::error::Result<String> {
+    let mut out = vec![format!("All accounts, in {}:", home.currency)];
+    let (mut balance, mut savings) = (Money::ZERO, Money::ZERO);
+    for bud in budgets {
+        let (c, n) = (bud.currency(), bud.notation());
+        let (bal, sav) = (bud.current_balance(), bud.total_savings());
+        out.push(format!(
+            "\t{} ({c}): {} balance, {} saved",
+            bud.account,
+            n.format(bal),
+            n.format(sav)
+        ));
+        balance = balance.checked_add(rates.convert(bal, c, home.currency)?)?;
+        savings = savings.checked_add(rates.convert(sav, c, home.currency)?)?;
+    }
+    out.push(format!(
+        "Total: {} balance, {} saved",
+        home.format(balance),
+        home.format(savings)
+    ));
+    Ok(out.join("\n"))
+}
+
+//history_view(): lists the commands that can be undone and redone
+pub fn history_view(bud: &Budget) -> String {
+    let history = bud.history();
+    let mut out = vec![String::from("Undo:")];
+    out.extend(history.undo_steps().map(|s| format!("\t{s}")));
+    out.push(String::from("Redo:"));
+    out.extend(history.redo_steps().map(|s| format!("\t{s}")));
+    out.join("\n")
+}
+
+//ledger_view(): renders the ledger, filtered by any expense name, YYYY-MM-DD date and/or #tag given
+pub fn ledger_view(bud: &Budget, filters: &[impl AsRef<str>]) -> Result<String> {
+    let mut expense = None;
+    let mut date = None;
+    let mut tag = None;
+    for filter in filters.iter().map(|f| f.as_ref()) {
+        match util::parse_date(filter) {
+            Ok(d) => date = Some(d),
+            Err(_) if filter.starts_with('#') => tag = Some(filter),
+            Err(_) => expense = Some(filter.to_ascii_lowercase()),
+        }
+    }
+
+    let entries: Vec<&ledger::Transaction> = bud
+        .ledger()
+        .entries()
+        .iter()
+        .filter(|t| expense.is_none() || t.kind.expense() == expense.as_deref())
+        .filter(|t| date.is_none_or(|d| t.timestamp.date() == d))
+        .filter(|t| tag.is_none_or(|tag| t.has_tag(tag)))
+        .collect();
+
+    if entries.is_empty() {
+        return Ok("No transactions recorded.".into());
+    }
+
+    let c = bud.notation();
+    let mut out: Vec<String> = entries.iter().map(|t| c.show(*t).to_string()).collect();
+    if let Some(name) = expense {
+        out.push(format!(
+            "Spent on {}: {}",
+            util::to_title_case(name),
+            c.format(ledger::Ledger::spent(entries.into_iter()))
+        ));
+    }
+    Ok(out.join("\n"))
+}
+
+//tags_view(): totals for every #tag used in memos this period, or the entries and total for one tag
+pub fn tags_view(bud: &Budget, tag: Option<&str>) -> String {
+    let c = bud.notation();
+    let line = |total: &ledger::TagTotal| {
+        format!(
+            "#{}: {} spent, {} received, {} outstanding ({} entries)",
+            total.tag,
+            c.format(total.spent),
+            c.format(total.received),
+            c.format(total.outstanding()),
+            total.entries
+        )
+    };
+    let totals = bud.tag_totals();
+    match tag {
+        Some(tag) => {
+            let tag = tag.trim_start_matches('#').to_lowercase();
+            let Some(total) = totals.iter().find(|t| t.tag == tag) else {
+                return format!("Nothing tagged #{tag} this period.");
+            };
+            let mut out: Vec<String> = bud
+                .ledger()
+                .since(bud.current_period().first_entry)
+                .filter(|t| t.has_tag(&tag))
+                .map(|t| c.show(t).to_string())
+                .collect();
+            out.push(line(total));
+            out.join("\n")
+        }
+        None if totals.is_empty() => String::from("No tags this period."),
+        None => {
+            let mut out = vec![String::from("Tags this period:")];
+            out.extend(totals.iter().map(|t| format!("\t{}", line(t))));
+            out.join("\n")
+        }
+    }
+}
+
+//execute_with_confirmation(): runs the commands, asking what to do if a payment cannot be afforded or goes past a limit,
+//the answer applying only to the command that asked, and a limit answer only to the limit, not the balance;
+//only asks at force level 0 and when attached to a terminal, otherwise it is the same as execute_cmds()
+pub fn execute_with_confirmation(
+    mut bud: Budget,
+    cmds: BudgetCommands,
+    force: u8,
+) -> nlbl::error::Result<Executed> {
+    use nlbl::error::Error as E;
+    if force > 0 || !Term::stderr().is_term() {
+        return execute_cmds(bud, cmds, force);
+    }
+
+    let mut outcomes = Vec::new();
+    for cmd in cmds {
+        let mut forces = force::Forces::from(force);
+        let executed = loop {
+            let (err, items) = match execute_cmd_with(bud.clone(), cmd.clone(), forces) {
+                Err(e @ E::CannotAfford { .. }) => {
+                    (e, ["cancel", "use remaining balance", "overdraft"])
+                }
+                Err(e @ E::OverLimit { .. }) => {
+                    (e, ["cancel", "pay up to the limit", "pay anyway"])
+                }
+                result => break result?,
+            };
+            let choice = Select::new()
+                .with_prompt(format!("{err}, what now?"))
+                .items(&items)
+                .default(0)
+                .interact_opt();
+            let raised = match choice {
+                Ok(Some(1)) => force::Force::UseRemaining,
+                Ok(Some(2)) => force::Force::Overdraft,
+                _ => return Err(err),
+            };
+            match err {
+                E::CannotAfford { .. } => forces.balance = raised,
+                _ => forces.limit = raised,
+            }
+        };
+        bud = executed.budget;
+        outcomes.extend(executed.outcomes);
+    }
+    Ok(Executed {
+        budget: bud,
+        outcomes,
+    })
+}
+
+pub fn run_interactive(mut bud: Budget, force: u8) -> Result<Budget> {
     let term = Term::stdout();
     term.set_title(APP_TITLE);
     term.clear_screen()?;
 
-    output(&term, &bud.to_string());
+    output(&term, &render::budget_view(&bud));
     output(&term, ">>  tip: enter 'help' to get started!\n");
 
     let mut user_input: String;
@@ -387,18 +1564,36 @@ pub fn run_interactive(mut bud: Budget) -> Result<Budget> {
             break;
         }
 
-        let cmd = parse_command(&user_input)?;
-        let err = match execute_cmd(bud.clone(), cmd, 0) {
-            Ok(b) => {
-                bud = b;
-                None
+        //a command or view that fails is shown as an error, the session and its changes carry on
+        let parsed = match parse_view(&user_input, &bud) {
+            Some(view) => view.map(|view| (BudgetCommand::Nothing, Some(view))),
+            None => parse_command(&user_input, bud.notation()).map(|cmd| (cmd, None)),
+        };
+        let executed = parsed.and_then(|(cmd, view)| {
+            execute_with_confirmation(bud.clone(), cmd.into(), force)
+                .map(|executed| (executed, view))
+                .map_err(Error::from)
+        });
+        let (outcomes, view, err) = match executed {
+            Ok((executed, view)) => {
+                bud = executed.budget;
+                (executed.outcomes, view, None)
             }
-            Err(e) => Some(e),
+            Err(e) => (Vec::new(), None, Some(e)),
         };
 
         term.clear_screen()?;
 
-        output(&term, &bud.to_string());
+        output(&term, &render::budget_view(&bud));
+
+        let outcomes = outcomes_view(&outcomes, bud.notation());
+        if !outcomes.is_empty() {
+            output(&term, &outcomes);
+        }
+
+        if let Some(v) = view {
+            output(&term, &v);
+        }
 
         if let Some(e) = err {
             output(&term, "error!");
@@ -1,11 +1,47 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
+use nlbl::debt::Compounding;
+use nlbl::expense::{self, ExpenseOption, Position};
+use nlbl::income::Frequency;
+use nlbl::locale::Notation;
+use nlbl::money::{Money, Percent};
 use nlbl::{util, BudgetCommand, BudgetCommands};
+use time::Date;
 
 #[derive(Debug)]
 pub struct AppConfig {
     pub app_settings: AppSettings,
     pub account_options: AccountOptions,
-    pub budget_commands: BudgetCommands,
+    //every command arg and its values, in the order they apply
+    //amounts are only parsed once the account, and so its currency, is loaded
+    pub command_args: Vec<(String, Vec<String>)>,
+}
+impl AppConfig {
+    //budget_commands(): turns the command args into BudgetCommands, parsing amounts in the given notation,
+    //or in the currency and locale being switched to if --currency or --locale are among them
+    pub fn budget_commands(&self, mut notation: Notation) -> Result<BudgetCommands> {
+        for (arg, vals) in self.command_args.iter() {
+            match arg.as_str() {
+                "currency" => notation.currency = vals[0].parse()?,
+                "locale" => notation.locale = vals[0].parse()?,
+                _ => {}
+            }
+        }
+        let budget_commands = self
+            .command_args
+            .iter()
+            .map(|(arg, vals)| {
+                command_from_arg(arg, vals.iter(), notation).map_err(|e| {
+                    println!("{e}");
+                    e
+                })
+            })
+            .collect::<Result<BudgetCommands>>()?;
+
+        #[cfg(debug_assertions)]
+        println!("[DEV] parsed budget commands: {budget_commands:?}");
+
+        Ok(budget_commands)
+    }
 }
 #[derive(Debug)]
 pub struct AppSettings {
@@ -15,11 +51,32 @@ pub struct AppSettings {
     pub dry_run: bool,
     pub verbosity: u8,
     pub json: bool,
+    //None = ledger not requested, otherwise the filters given to it
+    pub ledger: Option<Vec<String>>,
+    //None = periods not requested, otherwise the period or expense to show
+    pub periods: Option<Vec<String>>,
+    //None = due bills not requested, otherwise how many days ahead to look
+    pub due: Option<i64>,
+    pub goals: bool,
+    //None = no check, otherwise whether to repair what it finds
+    pub check: Option<bool>,
+    //None = tags not requested, otherwise the one tag to show
+    pub tags: Option<Option<String>>,
+    //save the account as a template with this name
+    pub save_template: Option<String>,
+    //None = debts not requested, otherwise the extra monthly payment to plan with
+    pub debts: Option<Option<String>>,
+    //None = no forecast, otherwise how many days ahead to simulate
+    pub forecast: Option<i64>,
+    //None = no consolidated report, otherwise the currency to report in, if not the account's
+    pub consolidate: Option<Option<String>>,
 }
 #[derive(Debug)]
 pub struct AccountOptions {
     pub account: Option<String>,
     pub create: bool,
+    //template to create the account from
+    pub template: Option<String>,
     pub default_rename: Option<String>,
 }
 
@@ -27,49 +84,43 @@ pub struct AccountOptions {
 pub fn command_from_arg<'a>(
     arg: &str,
     mut vals: impl Iterator<Item = &'a String> + Clone,
+    notation: Notation,
 ) -> Result<BudgetCommand> {
     use BudgetCommand as BC;
     //TODO: manage unwraps!!
     Ok(match arg {
         "paycheck" => BC::SetPaycheck {
-            amount: util::parse_dollar_string(vals.next().unwrap())?,
+            amount: notation.parse(vals.next().unwrap())?,
         },
         "paid" => {
-            let amount = if let Some(v) = vals.next() {
-                Some(util::parse_dollar_string(v)?)
-            } else {
-                None
-            };
-            BC::Paid { amount }
+            let (source, amount, memo) = source_amount_and_memo(vals, notation)?;
+            BC::Paid {
+                amount,
+                memo,
+                source,
+            }
         }
+        "income" => BC::SetIncomeSource {
+            name: vals.next().unwrap().into(),
+            amount: notation.parse(vals.next().unwrap())?,
+            frequency: match vals.next() {
+                Some(v) => v.parse()?,
+                None => Frequency::Monthly,
+            },
+        },
+        "remove_income" => BC::RemoveIncomeSource {
+            name: vals.next().unwrap().into(),
+        },
         "clear" => {
-            let mut inv = false;
-
-            let targets = vals
-                .inspect(|s| {
-                    if s.starts_with("!") {
-                        inv = true
-                    }
-                })
-                //i hate that this is cloned()
-                .cloned()
-                .collect();
+            let (targets, invert_selection) = clear_from_args(vals)?;
             BC::ClearExpense {
                 targets,
-                invert_selection: inv,
+                invert_selection,
             }
         }
         "edit" => {
             let target: String = vals.next().unwrap().into();
-            let mut new_name = None;
-            let mut new_amount = None;
-            vals.for_each(|v| {
-                if let Ok(n) = util::parse_dollar_string(v) {
-                    new_amount = Some(n);
-                } else {
-                    new_name = Some(v.clone());
-                }
-            });
+            let (new_name, new_amount) = edit_from_args(vals, notation)?;
             BC::EditExpense {
                 target,
                 new_name,
@@ -78,21 +129,312 @@ pub fn command_from_arg<'a>(
         }
         "new" => BC::NewExpense {
             name: vals.next().unwrap().into(),
-            amount: util::parse_dollar_string(vals.next().unwrap())?,
+            amount: notation.parse(vals.next().unwrap())?,
         },
         "pay" => {
             let name = vals.next().unwrap().into();
-            let amount = if let Some(v) = vals.next() {
-                Some(util::parse_dollar_string(v)?)
-            } else {
-                None
-            };
-            BC::PayExpense { name, amount }
+            let (amount, memo) = amount_and_memo(vals, notation)?;
+            BC::PayExpense { name, amount, memo }
+        }
+        "pay_split" => split_from_args(vals, notation)?,
+        "refund" => {
+            let name = vals.next().unwrap().into();
+            let amount = notation.parse(vals.next().unwrap())?;
+            let memo = memo_from_args(vals);
+            BC::Refund { name, amount, memo }
+        }
+        "reverse" => BC::ReversePayment {
+            id: id_from_arg(vals.next().unwrap())?,
+            memo: memo_from_args(vals),
+        },
+        "set" => {
+            let name: String = vals.next().unwrap().into();
+            let option = vals.next().unwrap();
+            let values: Vec<&str> = vals.map(|v| v.as_str()).collect();
+            BC::SetExpenseOption {
+                name,
+                option: ExpenseOption::from_args(option, &values)?,
+            }
         }
+        "move" => {
+            let name: String = vals.next().unwrap().into();
+            let values: Vec<&str> = vals.map(|v| v.as_str()).collect();
+            BC::MoveExpense {
+                name,
+                position: Position::from_args(&values)?,
+            }
+        }
+        "sort" => BC::SetExpenseSort {
+            sort: vals.next().unwrap().parse()?,
+        },
+        "auto_pay" => BC::SetAutomaticPayments {
+            enabled: expense::parse_toggle(vals.next().map(|v| v.as_str()))?,
+        },
+        "close_period" => BC::ClosePeriod {
+            next: vals.next().cloned(),
+        },
+        "goal" => {
+            let name: String = vals.next().unwrap().into();
+            let (target, deadline, share) = goal_from_args(vals, notation)?;
+            BC::SetSavingsGoal {
+                name,
+                target,
+                deadline,
+                share,
+            }
+        }
+        "remove_goal" => BC::RemoveSavingsGoal {
+            name: vals.next().unwrap().into(),
+        },
+        "deposit" => {
+            let goal = bucket_from_arg(vals.next().unwrap());
+            let (amount, memo) = amount_and_memo(vals, notation)?;
+            BC::Deposit { goal, amount, memo }
+        }
+        "withdraw" => {
+            let goal = bucket_from_arg(vals.next().unwrap());
+            let amount = notation.parse(vals.next().unwrap())?;
+            let memo = memo_from_args(vals);
+            BC::Withdraw { goal, amount, memo }
+        }
+        "debt" => debt_from_args(vals.next().unwrap(), vals, notation)?,
+        "remove_debt" => BC::RemoveDebt {
+            name: vals.next().unwrap().into(),
+        },
+        "pay_debt" => {
+            let name = vals.next().unwrap().into();
+            let (amount, memo) = amount_and_memo(vals, notation)?;
+            BC::PayDebt { name, amount, memo }
+        }
+        "transfer" => BC::Transfer {
+            from: bucket_from_arg(vals.next().unwrap()),
+            to: bucket_from_arg(vals.next().unwrap()),
+            amount: notation.parse(vals.next().unwrap())?,
+        },
+        "currency" => BC::SetCurrency {
+            currency: vals.next().unwrap().parse()?,
+        },
+        "locale" => BC::SetLocale {
+            locale: vals.next().unwrap().parse()?,
+        },
+        "undo" => BC::Undo {
+            steps: steps_from_arg(vals.next())?,
+        },
+        "redo" => BC::Redo {
+            steps: steps_from_arg(vals.next())?,
+        },
+        "repair" => BC::Repair,
         str => panic!("invalid BudgetCommand arg name !? {str}"),
     })
 }
 
+//MEMO_MARKER: starts a memo where an amount could go, as in "memo: march rent"
+pub const MEMO_MARKER: &str = "memo:";
+
+//amount_and_memo(): splits optional trailing values into an amount and a memo
+//the first value must be an amount unless it starts with MEMO_MARKER, anything after it is the memo
+pub fn amount_and_memo<S: AsRef<str>>(
+    vals: impl Iterator<Item = S>,
+    notation: Notation,
+) -> Result<(Option<Money>, Option<String>)> {
+    let mut vals = vals.peekable();
+    let amount = match vals.peek() {
+        Some(v) if !v.as_ref().starts_with(MEMO_MARKER) => Some(notation.parse(v.as_ref())?),
+        _ => None,
+    };
+    if amount.is_some() {
+        vals.next();
+    }
+    Ok((amount, memo_from_args(vals)))
+}
+
+//memo_from_args(): any remaining values joined into a memo, without a leading MEMO_MARKER
+pub fn memo_from_args<S: AsRef<str>>(vals: impl Iterator<Item = S>) -> Option<String> {
+    let memo: Vec<String> = vals.map(|v| v.as_ref().to_string()).collect();
+    let memo = memo.join(" ");
+    let memo = memo.strip_prefix(MEMO_MARKER).unwrap_or(&memo).trim();
+    (!memo.is_empty()).then(|| memo.to_string())
+}
+
+//RENAME_MARKER: starts the new name of an edited expense, as in "name:housing"
+pub const RENAME_MARKER: &str = "name:";
+
+//edit_from_args(): a new name, after RENAME_MARKER, and a new amount, in either order
+//anything not marked as a name must be an amount
+pub fn edit_from_args<S: AsRef<str>>(
+    vals: impl Iterator<Item = S>,
+    notation: Notation,
+) -> Result<(Option<String>, Option<Money>)> {
+    let mut new_name = None;
+    let mut new_amount = None;
+    for v in vals {
+        match v.as_ref().strip_prefix(RENAME_MARKER).map(str::trim) {
+            Some("") => {
+                return Err(Error::InvalidCommand(format!(
+                    "{RENAME_MARKER} needs a new name after it, like {RENAME_MARKER}housing"
+                )))
+            }
+            Some(name) => new_name = Some(name.to_string()),
+            None => new_amount = Some(notation.parse(v.as_ref())?),
+        }
+    }
+    Ok((new_name, new_amount))
+}
+
+//clear_from_args(): the expenses to clear, or with every name as "!name", the only ones not to clear
+pub fn clear_from_args<S: AsRef<str>>(
+    vals: impl Iterator<Item = S>,
+) -> Result<(Vec<String>, bool)> {
+    let vals: Vec<S> = vals.collect();
+    let kept: Vec<&str> = vals
+        .iter()
+        .filter_map(|v| v.as_ref().strip_prefix('!'))
+        .collect();
+    match kept.len() {
+        0 => Ok((vals.iter().map(|v| v.as_ref().to_string()).collect(), false)),
+        n if n == vals.len() && kept.iter().all(|name| !name.is_empty()) => {
+            Ok((kept.into_iter().map(String::from).collect(), true))
+        }
+        _ => Err(Error::InvalidCommand(String::from(
+            "clear takes either names to clear or !names to keep, not both",
+        ))),
+    }
+}
+
+//looks_like_amount(): whether a value starts the way an amount does, so failing to parse is a typo, not a name
+fn looks_like_amount(s: &str, notation: Notation) -> bool {
+    let s = s.trim_start();
+    s.starts_with(notation.currency.symbol())
+        || s.starts_with(|c: char| {
+            c.is_ascii_digit() || matches!(c, '+' | '-' | '(') || c == notation.locale.decimal()
+        })
+}
+
+//split_from_args(): pairs of an expense and the amount paid to it, anything after the last pair is the memo
+pub fn split_from_args<S: AsRef<str>>(
+    vals: impl Iterator<Item = S>,
+    notation: Notation,
+) -> Result<BudgetCommand> {
+    let vals: Vec<S> = vals.collect();
+    let mut parts = Vec::new();
+    let mut rest = vals.as_slice();
+    while let [name, amount, tail @ ..] = rest {
+        let amount = match notation.parse(amount.as_ref()) {
+            Ok(amount) => amount,
+            Err(e) if looks_like_amount(amount.as_ref(), notation) => return Err(e.into()),
+            Err(_) => break,
+        };
+        parts.push((name.as_ref().to_string(), amount));
+        rest = tail;
+    }
+    if parts.is_empty() {
+        return Err(Error::InvalidCommand(
+            "split needs an expense and an amount".into(),
+        ));
+    }
+    Ok(BudgetCommand::PaySplit {
+        parts,
+        memo: memo_from_args(rest.iter()),
+    })
+}
+
+//source_amount_and_memo(): like amount_and_memo(), but a leading value that is not an amount names an income source
+//a value with a letter in it, like 2nd-job, is a name, one without is a mistyped amount
+pub fn source_amount_and_memo<S: AsRef<str>>(
+    vals: impl Iterator<Item = S>,
+    notation: Notation,
+) -> Result<(Option<String>, Option<Money>, Option<String>)> {
+    let mut vals = vals.peekable();
+    let source = match vals.peek().map(|v| v.as_ref()) {
+        Some(v) if !v.starts_with(MEMO_MARKER) => match notation.parse(v) {
+            Ok(_) => None,
+            Err(e) if !v.contains(char::is_alphabetic) => return Err(e.into()),
+            Err(_) => vals.next().map(|v| v.as_ref().to_string()),
+        },
+        _ => None,
+    };
+    let (amount, memo) = amount_and_memo(vals, notation)?;
+    Ok((source, amount, memo))
+}
+
+//bucket_from_arg(): a savings goal name, where "savings" or "general" means general savings
+pub fn bucket_from_arg(s: impl AsRef<str>) -> Option<String> {
+    match s.as_ref().to_ascii_lowercase().as_str() {
+        "savings" | "general" => None,
+        _ => Some(s.as_ref().to_string()),
+    }
+}
+
+//goal_from_args(): optional savings goal values, a YYYY-MM-DD deadline, a share of each paycheck like 20%,
+//and/or a target amount
+pub fn goal_from_args<S: AsRef<str>>(
+    vals: impl Iterator<Item = S>,
+    notation: Notation,
+) -> Result<(Option<Money>, Option<Date>, Option<Percent>)> {
+    let mut target = None;
+    let mut deadline = None;
+    let mut share = None;
+    for v in vals {
+        let v = v.as_ref();
+        if v.ends_with('%') {
+            share = Some(v.parse()?);
+            continue;
+        }
+        match util::parse_date(v) {
+            Ok(date) => deadline = Some(date),
+            Err(_) => target = Some(notation.parse(v)?),
+        }
+    }
+    Ok((target, deadline, share))
+}
+
+//debt_from_args(): a debt's balance, APR and minimum payment, then optionally how it compounds
+pub fn debt_from_args<S: AsRef<str>>(
+    name: impl AsRef<str>,
+    mut vals: impl Iterator<Item = S>,
+    notation: Notation,
+) -> Result<BudgetCommand> {
+    let mut next = || {
+        vals.next()
+            .ok_or(Error::InvalidCommand("debt".into()))
+            .map(|v| v.as_ref().to_string())
+    };
+    let balance = notation.parse(&next()?)?;
+    let apr = next()?.parse()?;
+    let minimum = notation.parse(&next()?)?;
+    let compounding = match next() {
+        Ok(v) => v.parse()?,
+        Err(_) => Compounding::default(),
+    };
+    Ok(BudgetCommand::SetDebt {
+        name: name.as_ref().into(),
+        balance,
+        apr,
+        minimum,
+        compounding,
+    })
+}
+
+//steps_from_arg(): parses an optional undo/redo step count, defaulting to 1
+pub fn steps_from_arg(val: Option<impl AsRef<str>>) -> Result<usize> {
+    match val {
+        Some(v) => v
+            .as_ref()
+            .parse()
+            .map_err(|_| Error::InvalidCommand(v.as_ref().into())),
+        None => Ok(1),
+    }
+}
+
+//id_from_arg(): a ledger entry id, written as 4 or #4
+pub fn id_from_arg(val: impl AsRef<str>) -> Result<u64> {
+    let val = val.as_ref();
+    val.trim_start_matches('#')
+        .parse()
+        .map_err(|_| Error::InvalidCommand(val.into()))
+}
+
+#[allow(dead_code)]
 pub fn demo_defaults() -> (AppSettings, AccountOptions, BudgetCommands) {
     (
         AppSettings {
@@ -102,12 +444,92 @@ pub fn demo_defaults() -> (AppSettings, AccountOptions, BudgetCommands) {
             dry_run: false,
             verbosity: 1,
             json: false,
+            ledger: None,
+            periods: None,
+            due: None,
+            goals: false,
+            check: None,
+            tags: None,
+            save_template: None,
+            debts: None,
+            forecast: None,
+            consolidate: None,
         },
         AccountOptions {
             account: Some("Demo User".into()),
             create: false,
+            template: None,
             default_rename: None,
         },
         BudgetCommands::new(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dollars(n: i64) -> Money {
+        Money::from_cents(n * 100)
+    }
+
+    #[test]
+    fn memos_follow_an_amount_or_the_marker() {
+        let usd = Notation::default();
+        assert_eq!(
+            amount_and_memo(["12", "march", "rent"].iter(), usd).unwrap(),
+            (Some(dollars(12)), Some("march rent".into()))
+        );
+        assert_eq!(
+            amount_and_memo(["memo:", "march", "rent"].iter(), usd).unwrap(),
+            (None, Some("march rent".into()))
+        );
+        assert!(amount_and_memo(["march", "rent"].iter(), usd).is_err());
+        assert!(amount_and_memo(["12x"].iter(), usd).is_err());
+    }
+
+    #[test]
+    fn paid_names_a_source_only_with_letters() {
+        let usd = Notation::default();
+        assert_eq!(
+            source_amount_and_memo(["2nd-job", "50"].iter(), usd).unwrap(),
+            (Some("2nd-job".into()), Some(dollars(50)), None)
+        );
+        assert_eq!(
+            source_amount_and_memo(["50", "bonus"].iter(), usd).unwrap(),
+            (None, Some(dollars(50)), Some("bonus".into()))
+        );
+        assert!(source_amount_and_memo(["5,0"].iter(), usd).is_err());
+    }
+
+    #[test]
+    fn clear_inverts_only_with_every_name_marked() {
+        assert_eq!(
+            clear_from_args(["food", "rent"].iter()).unwrap(),
+            (vec!["food".into(), "rent".into()], false)
+        );
+        assert_eq!(
+            clear_from_args(["!food", "!rent"].iter()).unwrap(),
+            (vec!["food".into(), "rent".into()], true)
+        );
+        assert_eq!(clear_from_args([""; 0].iter()).unwrap(), (vec![], false));
+        assert!(clear_from_args(["food", "!rent"].iter()).is_err());
+        assert!(clear_from_args(["!"].iter()).is_err());
+    }
+
+    #[test]
+    fn edit_renames_only_with_the_marker() {
+        let usd = Notation::default();
+        assert_eq!(
+            edit_from_args(["name:housing", "600"].iter(), usd).unwrap(),
+            (Some("housing".into()), Some(dollars(600)))
+        );
+        assert_eq!(
+            edit_from_args(["600"].iter(), usd).unwrap(),
+            (None, Some(dollars(600)))
+        );
+        assert!(edit_from_args(["6OO"].iter(), usd).is_err());
+        assert!(edit_from_args(["housing"].iter(), usd).is_err());
+        assert!(edit_from_args(["name:"].iter(), usd).is_err());
+    }
+}
@@ -12,6 +12,8 @@ pub enum Error {
         #[source]
         cause: bson::de::Error,
     },
+    #[error("no template named {0}, built in or in data/templates")]
+    NoTemplateFound(String),
     #[error("not a save file: {file}")]
     SaveFormatMismatch { file: String },
     #[error("budget error: {0}")]
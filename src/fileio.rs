@@ -5,6 +5,8 @@ use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::Path;
 
+use nlbl::currency::ExchangeRates;
+use nlbl::template::Template;
 use nlbl::Budget;
 
 //version 1 with initial release
@@ -18,14 +20,6 @@ struct SaveFormat {
     data: Vec<u8>,
 }
 impl SaveFormat {
-    fn new(account: &str) -> Self {
-        SaveFormat {
-            app: env!("CARGO_PKG_NAME").into(),
-            version: env!("CARGO_PKG_VERSION").into(),
-            save_format: SAVE_FORMAT_VERSION,
-            data: Self::budget_into_bytes(Budget::new(account)),
-        }
-    }
     fn save(budget: Budget) -> Self {
         SaveFormat {
             app: env!("CARGO_PKG_NAME").into(),
@@ -65,15 +59,28 @@ pub fn handle_account_load(cfg: &AppConfig) -> Result<Budget> {
         change_default_account_display_name(name)?;
     }
 
+    let template = match &cfg.account_options.template {
+        Some(name) => Some(load_template(name)?),
+        None => None,
+    };
+
     let bud = if cfg.app_settings.mem_only {
-        Budget::new(cfg.account_options.account.as_deref().unwrap_or("default"))
+        let account = cfg.account_options.account.as_deref().unwrap_or("default");
+        match &template {
+            Some(template) => Budget::from_template(account, template)?,
+            None => Budget::new(account),
+        }
     } else {
         match &cfg.account_options.account {
             Some(acc) => match cfg.account_options.create {
-                true => create_new_budget_account(acc)?,
+                true => create_new_budget_account(acc, template.as_ref())?,
                 false => load_budget_account(acc)?,
             },
-            None => load_budget_account("default").unwrap_or(create_new_budget_account("default")?),
+            //only create the default account if it is missing, never overwrite it
+            None => match load_budget_account("default") {
+                Err(Error::NoAccountFound(_)) => create_new_budget_account("default", None)?,
+                loaded => loaded?,
+            },
         }
     };
 
@@ -87,10 +94,22 @@ pub fn load_budget_account(account: &str) -> Result<Budget> {
 
     //TODO: sanity check, check app name, format version, etc
 
-    bson::from_slice(&save.data).map_err(|e| Error::SaveBinaryCorrupted {
+    let mut bud: Budget = bson::from_slice(&save.data).map_err(|e| Error::SaveBinaryCorrupted {
         account: account.into(),
         cause: e,
-    })
+    })?;
+    //saves from before the ledger start it from their balance
+    bud.open_ledger();
+
+    //inconsistent data still loads, so it can be repaired
+    let findings = bud.check();
+    if !findings.is_empty() {
+        eprintln!(
+            "warning: account {account} has {} problem(s), see nlbt --check",
+            findings.len()
+        );
+    }
+    Ok(bud)
 }
 
 //returns SaveFormat object from given account's save file
@@ -121,6 +140,44 @@ fn read_account_file(account: &str) -> Result<File> {
     File::open(file_name).map_err(|_| Error::NoAccountFound(account.into()))
 }
 
+//every account with a save file, in name order
+pub fn load_all_accounts() -> Result<Vec<Budget>> {
+    let mut accounts: Vec<String> = fs::read_dir("data")?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            match path.extension()?.to_str()? {
+                "bson" => Some(path.file_stem()?.to_str()?.to_string()),
+                _ => None,
+            }
+        })
+        .collect();
+    accounts.sort();
+    accounts
+        .iter()
+        .map(|acc| load_budget_account(acc))
+        .collect()
+}
+
+//the user's exchange rate table in data/rates.json, or an empty one if there is none
+pub fn load_exchange_rates() -> Result<ExchangeRates> {
+    match fs::read_to_string("data/rates.json") {
+        Ok(json) => Ok(ExchangeRates::from_json(&json)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ExchangeRates::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+//a user template from data/templates, or else a built in one
+pub fn load_template(name: &str) -> Result<Template> {
+    match fs::read_to_string(format!("data/templates/{name}.json")) {
+        Ok(json) => Ok(Template::from_json(&json)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Template::builtin(name).ok_or(Error::NoTemplateFound(name.into()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
 // -- SAVING --
 
 //write a budget out to the account's save file
@@ -136,13 +193,27 @@ pub fn save_budget_to_account_file(budget: Budget) -> Result<()> {
     Ok(())
 }
 
+//write a template out to data/templates, replacing any with the same name
+pub fn save_template(template: &Template) -> Result<()> {
+    fs::create_dir_all("data/templates")?;
+    fs::write(
+        format!("data/templates/{}.json", template.name),
+        template.json()?,
+    )?;
+    Ok(())
+}
+
 // -- CREATION --
 
 //take in an account name, and create a save file, returning the new initialized Budget object
-pub fn create_new_budget_account(account: &str) -> Result<Budget> {
+//set up from the template, if one is given
+pub fn create_new_budget_account(account: &str, template: Option<&Template>) -> Result<Budget> {
+    let budget = match template {
+        Some(template) => Budget::from_template(account, template)?,
+        None => Budget::new(account),
+    };
     let mut file = make_account_file(account)?;
-
-    let save = SaveFormat::new(account);
+    let save = SaveFormat::save(budget);
     file.write_all(save.bytes().as_slice())?;
     file.flush()?;
 
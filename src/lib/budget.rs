@@ -1,204 +1,2356 @@
+use crate::check::Finding;
+use crate::currency::Currency;
+use crate::debt::{plan_payoff, Apr, Compounding, Debt, PayoffPlan, Strategy};
 use crate::error::{Error, Result};
+use crate::expense::{
+    Alert, ExpenseOption, ExpenseSettings, ExpenseSort, Position, ShareBasis, Threshold,
+};
+use crate::force::{Force, Forces};
+use crate::forecast::{Forecast, ForecastDay, ForecastEvent, MAX_FORECAST_DAYS};
+use crate::history::{History, Step};
+use crate::income::{Frequency, IncomeSource};
+use crate::ledger::{ExpenseChange, Ledger, TagTotal, TransactionKind};
+use crate::locale::{Locale, Notation};
+use crate::money::{Money, Percent};
+use crate::period::{default_period_name, ExpenseSnapshot, Period, PeriodSnapshot};
+use crate::report::{BudgetStatus, ExpenseReport, Report};
+use crate::savings::{GoalProgress, SavingsGoal};
+use crate::schedule::{DueBill, Recurrence, MAX_DUE_DAYS};
+use crate::template::{Template, TemplateExpense, TemplateGoal, TemplateIncome};
 use crate::util::*;
 
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-
-// const AUTOMATIC_PAYMENT_PREFIX: char = '*';
+use time::Date;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Budget {
     pub account: String,
-    current_balance: i32,
-    expected_income: i32,
-    expected_expenses: HashMap<String, i32>,
-    current_expenses: HashMap<String, i32>,
-    savings: i32,
+    #[serde(default)]
+    currency: Currency,
+    #[serde(default)]
+    locale: Locale,
+    current_balance: Money,
+    expected_income: Money,
+    #[serde(default)]
+    income_sources: HashMap<String, IncomeSource>,
+    //both kept in the same order, the order expenses are shown in by default
+    expected_expenses: IndexMap<String, Money>,
+    current_expenses: IndexMap<String, Money>,
+    savings: Money,
+    #[serde(default)]
+    savings_goals: HashMap<String, SavingsGoal>,
+    #[serde(default)]
+    debts: HashMap<String, Debt>,
+    //pay automatic expenses on payday, opt-in
+    #[serde(default)]
+    automatic_payments: bool,
+    #[serde(default)]
+    expense_settings: HashMap<String, ExpenseSettings>,
+    #[serde(default)]
+    expense_sort: ExpenseSort,
+    #[serde(default)]
+    period: Period,
+    #[serde(default)]
+    archived_periods: Vec<PeriodSnapshot>,
+    #[serde(default)]
+    ledger: Ledger,
+    #[serde(default)]
+    history: History,
 }
 impl Budget {
     //new(): factory method, returning a new Budget
     pub fn new(account: impl Into<String>) -> Budget {
         Budget {
             account: account.into(),
-            expected_income: 0,
-            current_balance: 0,
-            expected_expenses: HashMap::new(),
-            current_expenses: HashMap::new(),
-            savings: 0,
+            currency: Currency::default(),
+            locale: Locale::default(),
+            expected_income: Money::ZERO,
+            income_sources: HashMap::new(),
+            current_balance: Money::ZERO,
+            expected_expenses: IndexMap::new(),
+            current_expenses: IndexMap::new(),
+            savings: Money::ZERO,
+            savings_goals: HashMap::new(),
+            debts: HashMap::new(),
+            automatic_payments: false,
+            expense_settings: HashMap::new(),
+            expense_sort: ExpenseSort::default(),
+            period: Period::default(),
+            archived_periods: Vec::new(),
+            ledger: Ledger::default(),
+            history: History::default(),
+        }
+    }
+
+    //from_template(): a new Budget set up with a template's expenses, income and savings goals
+    pub fn from_template(account: impl Into<String>, template: &Template) -> Result<Budget> {
+        let mut bud = Budget::new(account);
+        bud.currency = template.currency;
+        bud.locale = template.locale;
+        if template.paycheck != Money::ZERO {
+            bud.set_income(template.paycheck)?;
+        }
+        for income in template.income_sources.iter() {
+            bud.set_income_source(&income.name, income.source.amount, income.source.frequency);
+        }
+        for expense in template.expenses.iter() {
+            bud.add_expense(&expense.name, expense.amount);
+            if expense.settings != ExpenseSettings::default() {
+                bud.expense_settings
+                    .insert(expense.name.to_ascii_lowercase(), expense.settings.clone());
+            }
+        }
+        bud.resolve_shares()?;
+        for goal in template.savings_goals.iter() {
+            bud.set_savings_goal(&goal.name, goal.target, None, goal.share);
+        }
+        bud.automatic_payments = template.automatic_payments;
+        Ok(bud)
+    }
+
+    //template(): this account's setup as a template, leaving out balances, payments and history
+    pub fn template(&self, name: impl Into<String>) -> Template {
+        Template {
+            name: name.into(),
+            description: format!("made from {}", self.account),
+            currency: self.currency,
+            locale: self.locale,
+            paycheck: self.expected_income,
+            income_sources: self
+                .income_sources()
+                .into_iter()
+                .map(|(name, source)| TemplateIncome {
+                    name: name.clone(),
+                    source: source.clone(),
+                })
+                .collect(),
+            expenses: self
+                .expected_expenses
+                .iter()
+                .map(|(name, amount)| TemplateExpense {
+                    name: name.clone(),
+                    amount: *amount,
+                    settings: self.expense_settings(name),
+                })
+                .collect(),
+            savings_goals: {
+                let mut goals: Vec<TemplateGoal> = self
+                    .savings_goals
+                    .iter()
+                    .map(|(name, goal)| TemplateGoal {
+                        name: name.clone(),
+                        target: goal.target,
+                        share: goal.share,
+                    })
+                    .collect();
+                goals.sort_by(|a, b| a.name.cmp(&b.name));
+                goals
+            },
+            automatic_payments: self.automatic_payments,
         }
     }
 
     pub fn json(&self) -> Result<String> {
         Ok(serde_json::to_string(self)?)
     }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    //notation(): how this account writes and reads amounts
+    pub fn notation(&self) -> Notation {
+        Notation::new(self.currency, self.locale)
+    }
+
+    pub fn current_balance(&self) -> Money {
+        self.current_balance
+    }
+
+    pub fn savings(&self) -> Money {
+        self.savings
+    }
+
+    pub fn expected_income(&self) -> Money {
+        self.expected_income
+    }
+
+    //income_sources(): every named income source, sorted by name
+    pub fn income_sources(&self) -> Vec<(&String, &IncomeSource)> {
+        let mut sources: Vec<_> = self.income_sources.iter().collect();
+        sources.sort_by_key(|(name, _)| *name);
+        sources
+    }
+
+    //monthly_income(): expected income per average month across every income source,
+    //or the paycheck if there are no sources
+    pub fn monthly_income(&self) -> Money {
+        if self.income_sources.is_empty() {
+            self.expected_income
+        } else {
+            self.income_sources.values().map(|s| s.monthly()).sum()
+        }
+    }
+
+    //report(): expected vs. actual for every expense, with totals, for frontends to render
+    pub fn report(&self) -> Report {
+        let today = now().date();
+        let mut expenses: Vec<ExpenseReport> = self
+            .expected_expenses
+            .iter()
+            .map(|(name, expected)| {
+                let expected = expected.saturating_add(self.period.carried_for(name));
+                let paid = self.current_expenses.get(name).copied().unwrap_or_default();
+                let settings = self.expense_settings(name);
+                ExpenseReport {
+                    name: name.clone(),
+                    expected,
+                    paid,
+                    remaining: expected.saturating_sub(paid),
+                    percent_used: (expected > Money::ZERO)
+                        .then(|| (paid.cents() as i128 * 100 / expected.cents() as i128) as i32),
+                    status: BudgetStatus::of(paid, expected),
+                    automatic: settings.automatic,
+                    next_due: settings.due.and_then(|rule| rule.next_due(today)),
+                    share: settings.share,
+                }
+            })
+            .collect();
+        Report::sort_expenses(&mut expenses, self.expense_sort);
+
+        let total_expected: Money = expenses.iter().map(|e| e.expected).sum();
+        let total_paid: Money = expenses.iter().map(|e| e.paid).sum();
+        let total_remaining: Money = expenses.iter().map(|e| e.remaining.max(Money::ZERO)).sum();
+        let monthly_income = self.monthly_income();
+        let set_aside: Money = self
+            .savings_goals
+            .values()
+            .filter_map(|goal| goal.share)
+            .map(|share| share.saturating_of(monthly_income))
+            .sum();
+        Report {
+            account: self.account.clone(),
+            period: self.period.name.clone(),
+            notation: self.notation(),
+            balance: self.current_balance,
+            paycheck: self.expected_income,
+            income_sources: self
+                .income_sources()
+                .into_iter()
+                .map(|(name, source)| (name.clone(), source.clone()))
+                .collect(),
+            monthly_income,
+            savings: self.savings,
+            goals: self.savings_goals(),
+            debts: self.debts(),
+            automatic_payments: self.automatic_payments,
+            expenses,
+            total_expected,
+            total_paid,
+            total_remaining,
+            unallocated: monthly_income
+                .saturating_sub(total_expected)
+                .saturating_sub(set_aside),
+            projected_balance: self.current_balance.saturating_sub(total_remaining),
+        }
+    }
+
+    //tag_totals(): what was spent and received under each #tag in memos this period
+    pub fn tag_totals(&self) -> Vec<TagTotal> {
+        Ledger::tag_totals(self.ledger.since(self.period.first_entry))
+    }
+
+    pub fn current_period(&self) -> &Period {
+        &self.period
+    }
+
+    //archived_periods(): every closed period, oldest first
+    pub fn archived_periods(&self) -> &[PeriodSnapshot] {
+        &self.archived_periods
+    }
+
+    pub fn archived_period(&self, name: &str) -> Option<&PeriodSnapshot> {
+        self.archived_periods.iter().find(|p| p.name == name)
+    }
+
+    //expense_settings(): the settings for an expense, or the defaults if none were set
+    pub fn expense_settings(&self, name: &str) -> ExpenseSettings {
+        self.expense_settings.get(name).cloned().unwrap_or_default()
+    }
+
+    //due_between(): every due date of every scheduled expense in [from, to], soonest first
+    //what was paid this period covers the earliest due dates first
+    pub fn due_between(&self, from: Date, to: Date) -> Vec<DueBill> {
+        let mut bills: Vec<DueBill> = self
+            .expense_settings
+            .iter()
+            .filter_map(|(name, settings)| Some((name, settings.due.as_ref()?)))
+            .flat_map(|(name, rule)| {
+                let expected = self.expected_for(name).unwrap_or_default();
+                let paid = self.current_expenses.get(name).copied().unwrap_or_default();
+                rule.due_between(from, to)
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(n, date)| DueBill {
+                        name: name.clone(),
+                        date,
+                        expected,
+                        paid: paid
+                            .saturating_sub(expected.saturating_mul(n as i64))
+                            .max(Money::ZERO),
+                        notation: self.notation(),
+                    })
+            })
+            .collect();
+        bills.sort_by(|a, b| a.date.cmp(&b.date).then(a.name.cmp(&b.name)));
+        bills
+    }
+
+    //due_within(): bills due from today through the next `days` days, up to MAX_DUE_DAYS
+    pub fn due_within(&self, days: i64) -> Vec<DueBill> {
+        let today = now().date();
+        self.due_between(today, add_days(today, days.min(MAX_DUE_DAYS)))
+    }
+
+    //forecast_between(): the balance simulated day by day over [from, to], from paydays of every income source
+    //(or the monthly paycheck), due dates of scheduled bills, and what is left of unscheduled expenses,
+    //which come due again at the start of every month; stops MAX_FORECAST_DAYS after `from`
+    pub fn forecast_between(&self, from: Date, to: Date) -> Forecast {
+        let to = to.min(add_days(from, MAX_FORECAST_DAYS));
+        let mut events: Vec<(Date, ForecastEvent)> = Vec::new();
+        let mut expect = |date: Date, name: &str, amount: Money| {
+            let name = name.to_string();
+            events.push((date, ForecastEvent { name, amount }));
+        };
+
+        if !self.income_sources.is_empty() || self.expected_income > Money::ZERO {
+            for (date, name, amount) in self.paydays_between(from, to) {
+                expect(date, &name, amount);
+            }
+        }
+
+        for bill in self.due_between(from, to) {
+            if !bill.covered() {
+                expect(
+                    bill.date,
+                    &bill.name,
+                    -bill.expected.saturating_sub(bill.paid),
+                );
+            }
+        }
+
+        let month_starts =
+            Recurrence::Monthly { day: 1 }.due_between(from.next_day().unwrap_or(from), to);
+        for (name, expected) in self.expected_expenses.iter() {
+            if self.expense_settings(name).due.is_some() {
+                continue;
+            }
+            let paid = self.current_expenses.get(name).copied().unwrap_or_default();
+            let left = self
+                .expected_for(name)
+                .unwrap_or_default()
+                .saturating_sub(paid);
+            if left > Money::ZERO {
+                expect(from, name, -left);
+            }
+            for date in month_starts.iter() {
+                expect(*date, name, -*expected);
+            }
+        }
+        events.sort_by(|(a, x), (b, y)| a.cmp(b).then(x.name.cmp(&y.name)));
+
+        let mut balance = self.current_balance;
+        let mut events = events.into_iter().peekable();
+        let mut days = Vec::new();
+        let mut date = from;
+        while date <= to {
+            let mut today = Vec::new();
+            while let Some((_, event)) = events.next_if(|(d, _)| *d == date) {
+                balance = balance.saturating_add(event.amount);
+                today.push(event);
+            }
+            days.push(ForecastDay {
+                date,
+                events: today,
+                balance,
+            });
+            match date.next_day() {
+                Some(next) => date = next,
+                None => break,
+            }
+        }
+        Forecast {
+            notation: self.notation(),
+            starting_balance: self.current_balance,
+            days,
+        }
+    }
+
+    //forecast(): the balance simulated from today through the next `days` days, up to MAX_FORECAST_DAYS
+    pub fn forecast(&self, days: i64) -> Forecast {
+        let today = now().date();
+        self.forecast_between(today, add_days(today, days))
+    }
+
+    //expected_for(): the expected amount of an expense, plus anything rolled over into this period
+    pub fn expected_for(&self, name: &str) -> Option<Money> {
+        self.expected_expenses
+            .get(name)
+            .map(|n| n.saturating_add(self.period.carried_for(name)))
+    }
+
+    //resolve_shares(): recomputes the expected amount of every expense set as a share of income,
+    //from the paycheck or from income received this period, changing none of them if one is out of range
+    fn resolve_shares(&mut self) -> Result<()> {
+        let received = Ledger::income(self.ledger.since(self.period.first_entry));
+        let mut resolved = Vec::new();
+        for (name, settings) in self.expense_settings.iter() {
+            let Some(share) = settings.share else {
+                continue;
+            };
+            let basis = match share.basis {
+                ShareBasis::Paycheck => self.expected_income,
+                ShareBasis::Received => received,
+            };
+            resolved.push((name, share.percent.of(basis)?));
+        }
+        for (name, amount) in resolved {
+            if let Some(expected) = self.expected_expenses.get_mut(name) {
+                *expected = amount;
+            }
+        }
+        Ok(())
+    }
 }
 impl Budget {
+    //snapshot(): a step back to the current state, leaving out the undo/redo history,
+    //and the ledger, which only needs to be cut back to where it is now
+    pub(crate) fn snapshot(&mut self, command: String) -> Step {
+        let history = std::mem::take(&mut self.history);
+        let ledger = std::mem::take(&mut self.ledger);
+        let state = Box::new(self.clone());
+        self.history = history;
+        self.ledger = ledger;
+        Step {
+            command,
+            state,
+            ledger: Some(self.ledger.tail(self.ledger.next_id())),
+        }
+    }
+
+    //record_step(): remembers the step back to before a command was applied, so it can be undone
+    pub(crate) fn record_step(&mut self, step: Step) {
+        self.history.push(step);
+    }
+
+    //undo(): restores the state from before the last applied command, returning its description
+    pub fn undo(&mut self) -> Result<String> {
+        let step = self.history.pop_undo().ok_or(Error::NothingToUndo)?;
+        let step = self.restore(step);
+        let command = step.command.clone();
+        self.history.push_redo(step);
+        Ok(command)
+    }
+
+    //redo(): re-applies the last undone command, returning its description
+    pub fn redo(&mut self) -> Result<String> {
+        let step = self.history.pop_redo().ok_or(Error::NothingToRedo)?;
+        let step = self.restore(step);
+        let command = step.command.clone();
+        self.history.push_undo(step);
+        Ok(command)
+    }
+
+    //restore(): swaps in the state held by a step, keeping the history, and returns the step back
+    fn restore(&mut self, step: Step) -> Step {
+        let Step {
+            command,
+            state,
+            ledger,
+        } = step;
+        let history = std::mem::take(&mut self.history);
+        let current_ledger = std::mem::take(&mut self.ledger);
+        let mut current = std::mem::replace(self, *state);
+        //one ledger is always the start of the other, so they differ from the shorter one's end
+        let tail = ledger.unwrap_or_else(|| {
+            let from = self.ledger.next_id().min(current_ledger.next_id());
+            self.ledger.tail(from)
+        });
+        self.history = history;
+        self.ledger = current_ledger;
+        let ledger = self.ledger.replace_tail(tail);
+        current.ledger = Ledger::default();
+        Step {
+            command,
+            state: Box::new(current),
+            ledger: Some(ledger),
+        }
+    }
+}
+impl Budget {
+    //set_currency(): changes the account currency, which only relabels the amounts,
+    //so it cannot change the number of decimal places once anything has been recorded
+    pub fn set_currency(&mut self, currency: Currency) -> Result<()> {
+        let recorded = self
+            .ledger
+            .entries()
+            .iter()
+            .any(|t| !matches!(t.kind, TransactionKind::Currency { .. }));
+        if recorded && currency.minor_units() != self.currency.minor_units() {
+            return Err(Error::CurrencyChange {
+                from: self.currency,
+                to: currency,
+            });
+        }
+        self.ledger.record(
+            TransactionKind::Currency {
+                from: self.currency,
+                to: currency,
+            },
+            None,
+        );
+        self.currency = currency;
+        Ok(())
+    }
+
+    //set_locale(): changes how amounts are written and read, without touching them
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
     //set_income(): sets expected_income to the new value
-    pub fn set_income(&mut self, cents: i32) {
-        self.expected_income = cents;
+    pub fn set_income(&mut self, cents: Money) -> Result<()> {
+        let from = std::mem::replace(&mut self.expected_income, cents);
+        if let Err(e) = self.resolve_shares() {
+            self.expected_income = from;
+            return Err(e);
+        }
+        self.ledger
+            .record(TransactionKind::Paycheck { from, to: cents }, None);
+        Ok(())
     }
 
     //add_income(): adds new value to expected_income
-    pub fn add_income(&mut self, cents: i32) {
-        self.set_income(self.expected_income + cents);
+    pub fn add_income(&mut self, cents: Money) -> Result<()> {
+        self.set_income(self.expected_income.checked_add(cents)?)
     }
 
     //get_paid(): adds expected_income to current_balance
-    pub fn get_paid(&mut self) {
-        self.get_paid_value(self.expected_income)
+    pub fn get_paid(&mut self, memo: Option<String>) -> Result<()> {
+        self.get_paid_value(self.expected_income, memo)
     }
 
     //get_paid_value(): adds given value to current_balance
-    pub fn get_paid_value(&mut self, cents: i32) {
-        self.current_balance += cents;
+    pub fn get_paid_value(&mut self, cents: Money, memo: Option<String>) -> Result<()> {
+        self.receive(cents, None, memo)
+    }
+
+    //get_paid_from(): adds a payment from an income source to current_balance, its usual amount if none is given
+    pub fn get_paid_from(
+        &mut self,
+        source: &str,
+        cents: Option<Money>,
+        memo: Option<String>,
+    ) -> Result<Money> {
+        let source = source.to_ascii_lowercase();
+        let cents = match self.income_sources.get(&source) {
+            Some(s) => cents.unwrap_or(s.amount),
+            None => return Err(Error::IncomeSourceDoesNotExist(source)),
+        };
+        self.receive(cents, Some(source), memo)?;
+        Ok(cents)
+    }
+
+    fn receive(
+        &mut self,
+        cents: Money,
+        source: Option<String>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        self.current_balance = self.current_balance.checked_add(cents)?;
+        self.ledger.record(
+            TransactionKind::Income {
+                amount: cents,
+                source,
+            },
+            memo,
+        );
+        self.resolve_shares()
+    }
+
+    //set_income_source(): creates or replaces a named income source
+    pub fn set_income_source(&mut self, name: &str, cents: Money, frequency: Frequency) {
+        let source = IncomeSource {
+            amount: cents,
+            frequency,
+        };
+        self.income_sources
+            .insert(name.to_ascii_lowercase(), source.clone());
+        self.ledger.record(
+            TransactionKind::IncomeSource {
+                name: name.to_ascii_lowercase(),
+                source: Some(source),
+            },
+            None,
+        );
+    }
+
+    pub fn remove_income_source(&mut self, name: &str) -> Result<()> {
+        let name = name.to_ascii_lowercase();
+        self.income_sources
+            .remove(&name)
+            .ok_or(Error::IncomeSourceDoesNotExist(name.clone()))?;
+        self.ledger
+            .record(TransactionKind::IncomeSource { name, source: None }, None);
+        Ok(())
     }
 
     pub fn clear_expense(&mut self, name: &str) -> Result<()> {
-        self.current_expenses
-            .get_mut(name)
-            .ok_or(Error::ExpenseDoesNotExist(name.into()))
-            .map(|c| *c = 0)
+        let name = name.to_ascii_lowercase();
+        let paid = self
+            .current_expenses
+            .get_mut(&name)
+            .ok_or(Error::ExpenseDoesNotExist(name.clone()))?;
+        let amount = std::mem::take(paid);
+        self.ledger.record(
+            TransactionKind::Clear {
+                expense: name,
+                amount,
+            },
+            None,
+        );
+        Ok(())
     }
 
     //refresh(): resets current_expenses
     pub fn full_refresh(&mut self) {
         for key in self.current_expenses.iter_mut() {
-            *key.1 = 0;
+            let amount = std::mem::take(key.1);
+            if amount != Money::ZERO {
+                self.ledger.record(
+                    TransactionKind::Clear {
+                        expense: key.0.clone(),
+                        amount,
+                    },
+                    None,
+                );
+            }
+        }
+    }
+
+    //clear_all_except(): resets current_expenses, except for the named expenses, which must all exist
+    pub fn clear_all_except(&mut self, names: &[String]) -> Result<()> {
+        let names: Vec<String> = names.iter().map(|n| n.to_ascii_lowercase()).collect();
+        if let Some(missing) = names
+            .iter()
+            .find(|name| !self.current_expenses.contains_key(*name))
+        {
+            return Err(Error::ExpenseDoesNotExist(missing.clone()));
         }
+        for (name, paid) in self.current_expenses.iter_mut() {
+            if names.contains(name) || *paid == Money::ZERO {
+                continue;
+            }
+            let amount = std::mem::take(paid);
+            self.ledger.record(
+                TransactionKind::Clear {
+                    expense: name.clone(),
+                    amount,
+                },
+                None,
+            );
+        }
+        Ok(())
     }
 
-    //TODO: automatic payments? default off, opt-in with a saved toggle? something like that
-    //make_automatic_payments(): adds up total of automatic payments, returns money left over (if positive -> Ok, if negative -> Err)
-    /*pub fn make_automatic_payments(&mut self, cents: i32) -> Result<i32> {
-        let mut autos: Vec<String> = Vec::new();
-        let mut payment = 0;
-        for key in self.expected_expenses.iter() {
-            if key.0.chars().next().unwrap() == AUTOMATIC_PAYMENT_PREFIX {
-                autos.push(key.0.clone());
-                payment += key.1;
+    //close_period(): archives the current period and starts a new one, rolling over unspent amounts where enabled
+    pub fn close_period(&mut self, next: Option<String>) -> Result<String> {
+        let taken = |name: &str| name == self.period.name || self.archived_period(name).is_some();
+        let next = next.unwrap_or_else(|| default_period_name(taken));
+        if taken(&next) {
+            return Err(Error::PeriodExists(next));
+        }
+
+        let names: Vec<&String> = self.expected_expenses.keys().collect();
+
+        let mut carried = HashMap::new();
+        let mut expenses = Vec::new();
+        for name in names {
+            let expected =
+                self.expected_expenses[name].checked_add(self.period.carried_for(name))?;
+            let actual = self.current_expenses.get(name).copied().unwrap_or_default();
+            let rolled_over = match self.expense_settings(name).rollover {
+                true => expected.checked_sub(actual)?.max(Money::ZERO),
+                false => Money::ZERO,
+            };
+            if rolled_over != Money::ZERO {
+                carried.insert(name.clone(), rolled_over);
             }
+            expenses.push(ExpenseSnapshot {
+                name: name.clone(),
+                expected,
+                actual,
+                rolled_over,
+            });
         }
-        if payment == 0 {
-            return Ok(-1);
+
+        let closed = std::mem::replace(&mut self.period, Period::new(&next, 0));
+        self.archived_periods.push(PeriodSnapshot {
+            income: Ledger::income(self.ledger.since(closed.first_entry)),
+            name: closed.name.clone(),
+            started: closed.started,
+            closed: now().date(),
+            balance: self.current_balance,
+            savings: self.savings,
+            expenses,
+            currency: self.currency,
+        });
+
+        for paid in self.current_expenses.values_mut() {
+            *paid = Money::ZERO;
         }
-        payment = cents - payment;
-        if payment < 0 {
-            Err(payment)
-        } else {
-            for name in autos {
-                let _ = self.make_static_payment(&name);
+        self.period.carried = carried;
+        self.period.first_entry = self.ledger.record(
+            TransactionKind::Period {
+                closed: closed.name.clone(),
+                opened: next.clone(),
+            },
+            None,
+        );
+        self.resolve_shares()?;
+
+        Ok(format!("Period {} closed, {next} started", closed.name))
+    }
+
+    //set_expense_option(): changes one setting of an existing expense
+    pub fn set_expense_option(&mut self, name: &str, option: ExpenseOption) -> Result<()> {
+        let name = name.to_ascii_lowercase();
+        if !self.expected_expenses.contains_key(&name) {
+            return Err(Error::ExpenseDoesNotExist(name));
+        }
+
+        let settings = self.expense_settings.entry(name.clone()).or_default();
+        match &option {
+            ExpenseOption::Rollover(enabled) => settings.rollover = *enabled,
+            ExpenseOption::Automatic(enabled) => settings.automatic = *enabled,
+            ExpenseOption::Due(rule) => settings.due = rule.clone(),
+            ExpenseOption::Share(share) => settings.share = *share,
+            ExpenseOption::Warn(percent) => settings.warn_at = *percent,
+            ExpenseOption::Limit(percent) => settings.limit_at = *percent,
+        }
+        if *settings == ExpenseSettings::default() {
+            self.expense_settings.remove(&name);
+        }
+        self.resolve_shares()?;
+
+        self.ledger.record(
+            TransactionKind::Edit {
+                expense: name,
+                change: ExpenseChange::Setting {
+                    setting: option.to_string(),
+                },
+            },
+            None,
+        );
+        Ok(())
+    }
+
+    //make_automatic_payments(): pays whatever is left on every automatic expense, in the budget's order,
+    //out of the given paycheck, returning what was paid and how much of the paycheck is left
+    //if the paycheck does not cover them, force decides between failing, paying what fits, or paying anyway
+    pub fn make_automatic_payments(
+        &mut self,
+        cents: Money,
+        force: Force,
+    ) -> Result<AutomaticPayments> {
+        let mut due: Vec<(String, Money)> = self
+            .expected_expenses
+            .keys()
+            .filter(|name| self.expense_settings(name).automatic)
+            .map(|name| {
+                let expected = self.expected_for(name).unwrap_or_default();
+                let paid = self.current_expenses.get(name).copied().unwrap_or_default();
+                (name.clone(), expected.saturating_sub(paid))
+            })
+            .filter(|(_, amount)| *amount > Money::ZERO)
+            .collect();
+
+        let mut total: Money = due.iter().map(|(_, amount)| amount).sum();
+        if total > cents {
+            match force {
+                Force::Overdraft => {}
+                Force::UseRemaining => {
+                    //pay in order until the paycheck runs out
+                    let mut left = cents.max(Money::ZERO);
+                    for (_, amount) in due.iter_mut() {
+                        *amount = (*amount).min(left);
+                        left = left.saturating_sub(*amount);
+                    }
+                    due.retain(|(_, amount)| *amount > Money::ZERO);
+                    total = due.iter().map(|(_, amount)| amount).sum();
+                }
+                Force::Confirm | Force::NoConfirm => {
+                    return Err(Error::CannotAfford {
+                        expense: "automatic payments".into(),
+                        amount: total,
+                        remaining_balance: cents,
+                        notation: self.notation(),
+                    })
+                }
             }
-            Ok(payment)
         }
+
+        let mut alerts = Vec::new();
+        for (name, amount) in due.iter() {
+            //already checked against the paycheck above
+            let payment = self.make_dynamic_payment(
+                name,
+                *amount,
+                Some("automatic".into()),
+                Force::Overdraft,
+            )?;
+            alerts.extend(payment.alerts);
+        }
+
+        Ok(AutomaticPayments {
+            paid: due,
+            remaining: cents.saturating_sub(total),
+            alerts,
+            notation: self.notation(),
+        })
+    }
+
+    //set_automatic_payments(): turns automatic payments on payday on or off for the whole account
+    pub fn set_automatic_payments(&mut self, enabled: bool) {
+        self.automatic_payments = enabled;
+    }
+
+    pub fn automatic_payments(&self) -> bool {
+        self.automatic_payments
+    }
+
+    //move_expense(): changes where an expense comes in the budget's order, returning its new 1-based position
+    pub fn move_expense(&mut self, name: &str, position: Position) -> Result<usize> {
+        let name = name.to_ascii_lowercase();
+        let from = self
+            .expected_expenses
+            .get_index_of(&name)
+            .ok_or(Error::ExpenseDoesNotExist(name.clone()))?;
+        let to = position.index(from, self.expected_expenses.len());
+        if from == to {
+            return Ok(to + 1);
+        }
+        self.expected_expenses.move_index(from, to);
+        if let Some(current) = self.current_expenses.get_index_of(&name) {
+            self.current_expenses.move_index(current, to);
+        }
+        self.ledger.record(
+            TransactionKind::Edit {
+                expense: name,
+                change: ExpenseChange::Moved {
+                    from: from + 1,
+                    to: to + 1,
+                },
+            },
+            None,
+        );
+        Ok(to + 1)
+    }
+
+    pub fn expense_sort(&self) -> ExpenseSort {
+        self.expense_sort
+    }
+
+    //set_expense_sort(): how expenses are ordered when shown, without changing the budget's own order
+    pub fn set_expense_sort(&mut self, sort: ExpenseSort) {
+        self.expense_sort = sort;
     }
-    */
 
     //add_expense(): creates a new expense in both HashMaps, with the new value as the expected value in expected_expenses
-    pub fn add_expense(&mut self, name: &str, cents: i32) {
+    pub fn add_expense(&mut self, name: &str, cents: Money) {
         self.expected_expenses
             .insert(name.to_string().to_ascii_lowercase(), cents);
         self.current_expenses
-            .insert(name.to_string().to_ascii_lowercase(), 0);
+            .insert(name.to_string().to_ascii_lowercase(), Money::ZERO);
+        self.ledger.record(
+            TransactionKind::Edit {
+                expense: name.to_ascii_lowercase(),
+                change: ExpenseChange::Created { amount: cents },
+            },
+            None,
+        );
     }
 
-    pub fn edit_expense(&mut self, name: &str, cents: i32) -> Result<()> {
-        self.expected_expenses
-            .get_mut(name)
-            .ok_or(Error::ExpenseDoesNotExist(name.into()))
-            .map(|c| *c = cents)
+    //edit_expense(): sets a fixed expected amount, replacing any share of income it was set to
+    pub fn edit_expense(&mut self, name: &str, cents: Money) -> Result<()> {
+        let name = name.to_ascii_lowercase();
+        let amount = self
+            .expected_expenses
+            .get_mut(&name)
+            .ok_or(Error::ExpenseDoesNotExist(name.clone()))?;
+        let from = std::mem::replace(amount, cents);
+        if let Some(settings) = self.expense_settings.get_mut(&name) {
+            settings.share = None;
+            if *settings == ExpenseSettings::default() {
+                self.expense_settings.remove(&name);
+            }
+        }
+        self.ledger.record(
+            TransactionKind::Edit {
+                expense: name,
+                change: ExpenseChange::Amount { from, to: cents },
+            },
+            None,
+        );
+        Ok(())
     }
 
     pub fn rename_expense(&mut self, name: &str, new_name: &str) -> Result<()> {
-        let amount = self
+        let (name, new_name) = (name.to_ascii_lowercase(), new_name.to_ascii_lowercase());
+        if !self.expected_expenses.contains_key(&name) {
+            return Err(Error::ExpenseDoesNotExist(name));
+        }
+        if new_name != name && self.expected_expenses.contains_key(&new_name) {
+            return Err(Error::ExpenseExists(new_name));
+        }
+
+        //renamed expenses keep their place in the order
+        let (index, _, amount) = self
             .expected_expenses
-            .remove(name)
-            .ok_or(Error::ExpenseDoesNotExist(name.into()))?;
-        self.expected_expenses.insert(new_name.into(), amount);
+            .shift_remove_full(&name)
+            .ok_or(Error::ExpenseDoesNotExist(name.clone()))?;
+        self.expected_expenses
+            .shift_insert(index, new_name.clone(), amount);
 
-        let amount = self
+        let (index, _, amount) = self
             .current_expenses
-            .remove(name)
-            .ok_or(Error::ExpenseDoesNotExist(name.into()))?;
-        self.current_expenses.insert(new_name.into(), amount);
+            .shift_remove_full(&name)
+            .ok_or(Error::ExpenseDoesNotExist(name.clone()))?;
+        self.current_expenses
+            .shift_insert(index, new_name.clone(), amount);
+
+        if let Some(settings) = self.expense_settings.remove(&name) {
+            self.expense_settings.insert(new_name.clone(), settings);
+        }
+        if let Some(carried) = self.period.carried.remove(&name) {
+            self.period.carried.insert(new_name.clone(), carried);
+        }
+
+        self.ledger.record(
+            TransactionKind::Edit {
+                expense: new_name,
+                change: ExpenseChange::Renamed { from: name },
+            },
+            None,
+        );
         Ok(())
     }
 
-    //make_static_payment(): makes a payment into current_expenses, with the value from expected_expenses
-    pub fn make_static_payment(&mut self, name: &str) -> Result<String> {
-        match self.expected_expenses.get(name) {
-            Some(n) => self.make_dynamic_payment(name, *n),
-            None => Err(Error::ExpenseDoesNotExist(name.into())),
+    //afford(): checks an amount leaving current_balance against the force level, returning how much may be paid
+    pub fn afford(&self, expense: &str, cents: Money, force: Force) -> Result<Money> {
+        if cents <= self.current_balance || force == Force::Overdraft {
+            return Ok(cents);
+        }
+        if force == Force::UseRemaining && self.current_balance > Money::ZERO {
+            return Ok(self.current_balance);
+        }
+        Err(Error::CannotAfford {
+            expense: expense.into(),
+            amount: cents,
+            remaining_balance: self.current_balance,
+            notation: self.notation(),
+        })
+    }
+
+    //within_limit(): checks a payment to an expense that already has `paid` against its limit, if it has one,
+    //returning how much may be paid, force works as in afford(), UseRemaining pays only what is left under the limit
+    fn within_limit(&self, name: &str, paid: Money, cents: Money, force: Force) -> Result<Money> {
+        let Some(limit) = self.expense_settings(name).limit_at else {
+            return Ok(cents);
+        };
+        let expected = self.expected_for(name).unwrap_or_default();
+        let allowed = limit.of(expected)?.saturating_sub(paid).max(Money::ZERO);
+        if cents <= allowed || force == Force::Overdraft {
+            return Ok(cents);
+        }
+        if force == Force::UseRemaining && allowed > Money::ZERO {
+            return Ok(allowed);
+        }
+        Err(Error::OverLimit {
+            expense: name.into(),
+            amount: cents,
+            limit,
+            allowed,
+            notation: self.notation(),
+        })
+    }
+
+    //alerts(): every threshold of an expense crossed since it had `before` paid,
+    //a warning once paid reaches its percentage, a limit once paid goes past it
+    fn alerts(&self, name: &str, before: Money) -> Vec<Alert> {
+        let settings = self.expense_settings(name);
+        let expected = self.expected_for(name).unwrap_or_default();
+        let paid = self.current_expenses.get(name).copied().unwrap_or_default();
+        [
+            (Threshold::Warning, settings.warn_at),
+            (Threshold::Limit, settings.limit_at),
+        ]
+        .into_iter()
+        .filter_map(|(threshold, percent)| {
+            let percent = percent?;
+            let at = percent.saturating_of(expected);
+            let crossed = match threshold {
+                Threshold::Warning => before < at && paid >= at,
+                Threshold::Limit => before <= at && paid > at,
+            };
+            crossed.then(|| Alert {
+                expense: name.into(),
+                threshold,
+                percent,
+                paid,
+                expected,
+            })
+        })
+        .collect()
+    }
+
+    //make_static_payment(): makes a payment into current_expenses, with the expected amount, including anything rolled over
+    pub fn make_static_payment(
+        &mut self,
+        name: &str,
+        memo: Option<String>,
+        force: impl Into<Forces>,
+    ) -> Result<Payment> {
+        let name = name.to_ascii_lowercase();
+        match self.expected_for(&name) {
+            Some(n) => self.make_dynamic_payment(&name, n, memo, force),
+            None => Err(Error::ExpenseDoesNotExist(name)),
         }
     }
 
     //make_dynamic_payment(): makes a payment into current_expenses, with the given value
-    pub fn make_dynamic_payment(&mut self, name: &str, cents: i32) -> Result<String> {
+    //payments beyond current_balance, or past the expense's limit, fail, are trimmed, or go through, depending on force
+    pub fn make_dynamic_payment(
+        &mut self,
+        name: &str,
+        cents: Money,
+        memo: Option<String>,
+        force: impl Into<Forces>,
+    ) -> Result<Payment> {
+        let force = force.into();
+        let cents = self.positive(cents)?;
         let name = name.to_ascii_lowercase();
+        if !self.current_expenses.contains_key(&name) {
+            return Err(Error::ExpenseDoesNotExist(name));
+        }
+        let paid = self
+            .current_expenses
+            .get(&name)
+            .copied()
+            .unwrap_or_default();
+        let cents = self.within_limit(&name, paid, cents, force.limit)?;
+        let cents = self.afford(&name, cents, force.balance)?;
+
+        let balance = self.current_balance.checked_sub(cents)?;
         if let Some(n) = self.current_expenses.get_mut(&name) {
-            self.current_balance -= cents;
-            *n += cents;
+            *n = n.checked_add(cents)?;
+            self.current_balance = balance;
         } else {
             return Err(Error::ExpenseDoesNotExist(name));
         };
+        self.ledger.record(
+            TransactionKind::Payment {
+                expense: name.clone(),
+                amount: cents,
+            },
+            memo,
+        );
+
+        Ok(Payment {
+            alerts: self.alerts(&name, paid),
+            paid: vec![(name, cents)],
+            notation: self.notation(),
+        })
+    }
+
+    //make_split_payment(): pays several expenses out of one payment, like a receipt covering groceries and household
+    //nothing changes unless every expense exists and the total is affordable, a split is never trimmed to the balance
+    pub fn make_split_payment(
+        &mut self,
+        parts: &[(String, Money)],
+        memo: Option<String>,
+        force: impl Into<Forces>,
+    ) -> Result<Payment> {
+        let parts: Vec<(String, Money)> = parts
+            .iter()
+            .map(|(name, cents)| (name.to_ascii_lowercase(), *cents))
+            .collect();
+        if let Some((name, _)) = parts
+            .iter()
+            .find(|(name, _)| !self.current_expenses.contains_key(name))
+        {
+            return Err(Error::ExpenseDoesNotExist(name.clone()));
+        }
+        for (_, cents) in parts.iter() {
+            self.positive(*cents)?;
+        }
+        let total = parts
+            .iter()
+            .try_fold(Money::ZERO, |total, (_, cents)| total.checked_add(*cents))?;
+        let names: Vec<&str> = parts.iter().map(|(name, _)| name.as_str()).collect();
+        let untrimmed = |force: Force| match force {
+            Force::UseRemaining => Force::NoConfirm,
+            force => force,
+        };
+        let Forces { balance, limit } = force.into();
+        self.afford(&names.join(", "), total, untrimmed(balance))?;
+
+        //each part is checked against its expense's limit as paid so far, including earlier parts
+        let before = self.current_expenses.clone();
+        let balance = self.current_balance.checked_sub(total)?;
+        let mut expenses = self.current_expenses.clone();
+        for (name, cents) in parts.iter() {
+            let paid = expenses.get_mut(name).expect("expense-checked-above");
+            self.within_limit(name, *paid, *cents, untrimmed(limit))?;
+            *paid = paid.checked_add(*cents)?;
+        }
+        self.current_expenses = expenses;
+        self.current_balance = balance;
+        for (name, cents) in parts.iter() {
+            self.ledger.record(
+                TransactionKind::Payment {
+                    expense: name.clone(),
+                    amount: *cents,
+                },
+                memo.clone(),
+            );
+        }
+
+        let mut alerts: Vec<Alert> = Vec::new();
+        for (i, (name, _)) in parts.iter().enumerate() {
+            //an expense paid more than once in the split alerts once
+            if parts[..i].iter().all(|(other, _)| other != name) {
+                alerts.extend(self.alerts(name, before.get(name).copied().unwrap_or_default()));
+            }
+        }
+        Ok(Payment {
+            paid: parts,
+            alerts,
+            notation: self.notation(),
+        })
+    }
+
+    //refund(): credits an amount back to the balance and takes it off what was paid to an expense
+    //refunds beyond what was paid fail, are trimmed to it, or leave the expense below zero, depending on force
+    pub fn refund(
+        &mut self,
+        name: &str,
+        cents: Money,
+        memo: Option<String>,
+        force: Force,
+    ) -> Result<String> {
+        let cents = self.positive(cents)?;
+        let name = name.to_ascii_lowercase();
+        let cents = self.refundable(&name, cents, force)?;
+        self.credit_expense(&name, cents, None, memo)?;
+        Ok(format!(
+            "Refund made: {} from {}",
+            self.notation().format(cents),
+            to_title_case(name)
+        ))
+    }
+
+    //reverse_payment(): undoes the payment with the given ledger id, once
+    //payments from closed periods only go back into the balance, as their period's expenses are archived
+    pub fn reverse_payment(
+        &mut self,
+        id: u64,
+        memo: Option<String>,
+        force: Force,
+    ) -> Result<String> {
+        let (name, cents) = match self.ledger.get(id).map(|t| &t.kind) {
+            Some(TransactionKind::Payment { expense, amount }) => (expense.clone(), *amount),
+            Some(_) => return Err(Error::NotAPayment(id)),
+            None => return Err(Error::TransactionDoesNotExist(id)),
+        };
+        if self.ledger.reversal_of(id).is_some() {
+            return Err(Error::AlreadyReversed(id));
+        }
 
+        if id >= self.period.first_entry {
+            if self.refundable(&name, cents, force)? != cents {
+                return Err(self.refund_exceeds_paid(&name, cents));
+            }
+            self.credit_expense(&name, cents, Some(id), memo)?;
+        } else {
+            self.current_balance = self.current_balance.checked_add(cents)?;
+            self.ledger.record(
+                TransactionKind::Refund {
+                    expense: name.clone(),
+                    amount: cents,
+                    reverses: Some(id),
+                },
+                memo,
+            );
+        }
         Ok(format!(
-            "Payment made: {} to {}",
-            format_dollars(cents),
+            "Payment #{id} reversed: {} back from {}",
+            self.notation().format(cents),
             to_title_case(name)
         ))
     }
 
+    //refundable(): how much of a refund an expense can take, never more than was paid to it unless overdrafting
+    fn refundable(&self, name: &str, cents: Money, force: Force) -> Result<Money> {
+        let paid = *self
+            .current_expenses
+            .get(name)
+            .ok_or_else(|| Error::ExpenseDoesNotExist(name.into()))?;
+        if cents <= paid || force == Force::Overdraft {
+            return Ok(cents);
+        }
+        if force == Force::UseRemaining && paid > Money::ZERO {
+            return Ok(paid);
+        }
+        Err(self.refund_exceeds_paid(name, cents))
+    }
+
+    fn refund_exceeds_paid(&self, name: &str, cents: Money) -> Error {
+        Error::RefundExceedsPaid {
+            expense: name.into(),
+            amount: cents,
+            paid: self.current_expenses.get(name).copied().unwrap_or_default(),
+            notation: self.notation(),
+        }
+    }
+
+    //credit_expense(): moves an amount from an expense back into the balance and records it
+    fn credit_expense(
+        &mut self,
+        name: &str,
+        cents: Money,
+        reverses: Option<u64>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let balance = self.current_balance.checked_add(cents)?;
+        let paid = self
+            .current_expenses
+            .get_mut(name)
+            .ok_or_else(|| Error::ExpenseDoesNotExist(name.into()))?;
+        *paid = paid.checked_sub(cents)?;
+        self.current_balance = balance;
+        self.ledger.record(
+            TransactionKind::Refund {
+                expense: name.into(),
+                amount: cents,
+                reverses,
+            },
+            memo,
+        );
+        Ok(())
+    }
+
     //save(): adds the given amount into savings
-    pub fn save(&mut self, cents: i32) -> Result<String> {
+    pub fn save(&mut self, cents: Money, memo: Option<String>) -> Result<String> {
+        self.deposit(None, cents, memo)
+    }
+
+    //save_all(): moves current_balance to savings
+    pub fn save_all(&mut self, memo: Option<String>) -> Result<String> {
+        self.save(self.current_balance, memo)
+    }
+
+    //deposit(): moves the given amount from current_balance into a savings goal, or general savings if None
+    pub fn deposit(
+        &mut self,
+        goal: Option<&str>,
+        cents: Money,
+        memo: Option<String>,
+    ) -> Result<String> {
+        let cents = self.positive(cents)?;
+        let goal = goal.map(|g| g.to_ascii_lowercase());
         if self.current_balance < cents {
-            Err(Error::CannotAfford {
-                expense: "savings".into(),
+            return Err(Error::CannotAfford {
+                expense: goal.unwrap_or("savings".into()),
                 amount: cents,
                 remaining_balance: self.current_balance,
+                notation: self.notation(),
+            });
+        }
+
+        let balance = self.current_balance.checked_sub(cents)?;
+        let bucket = self.savings_bucket(goal.as_deref())?;
+        *bucket = bucket.checked_add(cents)?;
+        self.current_balance = balance;
+        self.ledger.record(
+            TransactionKind::Savings {
+                amount: cents,
+                goal: goal.clone(),
+            },
+            memo,
+        );
+        Ok(match goal {
+            Some(name) => format!(
+                "{} saved to {}!",
+                self.notation().format(cents),
+                to_title_case(name)
+            ),
+            None => format!("{} saved!", self.notation().format(cents)),
+        })
+    }
+
+    //withdraw(): moves the given amount out of a savings goal, or general savings if None, into current_balance
+    pub fn withdraw(
+        &mut self,
+        goal: Option<&str>,
+        cents: Money,
+        memo: Option<String>,
+    ) -> Result<String> {
+        let cents = self.positive(cents)?;
+        let goal = goal.map(|g| g.to_ascii_lowercase());
+        let balance = self.current_balance.checked_add(cents)?;
+        self.take_from_bucket(goal.as_deref(), cents)?;
+        self.current_balance = balance;
+        self.ledger.record(
+            TransactionKind::Withdrawal {
+                amount: cents,
+                goal: goal.clone(),
+            },
+            memo,
+        );
+        Ok(format!(
+            "{} withdrawn from {}",
+            self.notation().format(cents),
+            to_title_case(goal.unwrap_or("savings".into()))
+        ))
+    }
+
+    //transfer(): moves the given amount between savings goals, None being general savings
+    pub fn transfer(
+        &mut self,
+        from: Option<&str>,
+        to: Option<&str>,
+        cents: Money,
+    ) -> Result<String> {
+        let cents = self.positive(cents)?;
+        let from = from.map(|g| g.to_ascii_lowercase());
+        let to = to.map(|g| g.to_ascii_lowercase());
+        //check the destination first, so a missing goal or overflow changes nothing
+        self.savings_bucket(to.as_deref())?.checked_add(cents)?;
+        self.take_from_bucket(from.as_deref(), cents)?;
+        let bucket = self.savings_bucket(to.as_deref())?;
+        *bucket = bucket.checked_add(cents)?;
+        self.ledger.record(
+            TransactionKind::Transfer {
+                amount: cents,
+                from: from.clone(),
+                to: to.clone(),
+            },
+            None,
+        );
+        Ok(format!(
+            "{} moved from {} to {}",
+            self.notation().format(cents),
+            to_title_case(from.unwrap_or("savings".into())),
+            to_title_case(to.unwrap_or("savings".into()))
+        ))
+    }
+
+    //set_savings_goal(): creates a savings goal, or changes the target, deadline and paycheck share of an existing one
+    pub fn set_savings_goal(
+        &mut self,
+        name: &str,
+        target: Option<Money>,
+        deadline: Option<Date>,
+        share: Option<Percent>,
+    ) {
+        let name = name.to_ascii_lowercase();
+        let goal = self.savings_goals.entry(name.clone()).or_default();
+        goal.target = target;
+        goal.deadline = deadline;
+        goal.share = share;
+        let goal = goal.clone();
+        self.ledger.record(
+            TransactionKind::Goal {
+                name,
+                goal: Some(goal),
+            },
+            None,
+        );
+    }
+
+    //remove_savings_goal(): deletes a savings goal, moving whatever it held into general savings
+    pub fn remove_savings_goal(&mut self, name: &str) -> Result<()> {
+        let name = name.to_ascii_lowercase();
+        let goal = self
+            .savings_goals
+            .remove(&name)
+            .ok_or(Error::SavingsGoalDoesNotExist(name.clone()))?;
+        self.savings = self.savings.checked_add(goal.saved)?;
+        self.ledger
+            .record(TransactionKind::Goal { name, goal: None }, None);
+        Ok(())
+    }
+
+    //save_shares(): sets aside every savings goal's share of a paycheck, as far as the balance goes,
+    //returning each goal and what went into it, by name
+    pub fn save_shares(&mut self, cents: Money) -> Result<Vec<(String, Money)>> {
+        let mut shares: Vec<(String, Percent)> = self
+            .savings_goals
+            .iter()
+            .filter_map(|(name, goal)| Some((name.clone(), goal.share?)))
+            .collect();
+        shares.sort();
+
+        let mut saved = Vec::new();
+        for (name, share) in shares {
+            let amount = share.of(cents)?.min(self.current_balance);
+            if amount > Money::ZERO {
+                self.deposit(Some(&name), amount, Some("automatic".into()))?;
+                saved.push((name, amount));
+            }
+        }
+        Ok(saved)
+    }
+
+    //savings_goals(): progress towards every savings goal, sorted by name
+    pub fn savings_goals(&self) -> Vec<GoalProgress> {
+        let mut goals: Vec<GoalProgress> = self
+            .savings_goals
+            .iter()
+            .map(|(name, goal)| {
+                let paychecks_left = goal.deadline.map(|d| self.paychecks_until(d));
+                let remaining = goal
+                    .target
+                    .map(|t| t.saturating_sub(goal.saved).max(Money::ZERO));
+                //rounded up, so the goal is met by the deadline
+                let per_paycheck = match (remaining, paychecks_left) {
+                    (Some(r), Some(p)) if p > 0 => {
+                        Some(Money::from_cents((r.cents() + p as i64 - 1) / p as i64))
+                    }
+                    _ => None,
+                };
+                GoalProgress {
+                    name: name.clone(),
+                    saved: goal.saved,
+                    target: goal.target,
+                    deadline: goal.deadline,
+                    paychecks_left,
+                    per_paycheck,
+                    share: goal.share,
+                    notation: self.notation(),
+                }
             })
-        } else {
-            self.current_balance -= cents;
-            self.savings += cents;
-            Ok(format!("{} saved!", format_dollars(cents)))
+            .collect();
+        goals.sort_by(|a, b| a.name.cmp(&b.name));
+        goals
+    }
+
+    //total_savings(): general savings plus every savings goal
+    pub fn total_savings(&self) -> Money {
+        self.savings
+            .saturating_add(self.savings_goals.values().map(|g| g.saved).sum())
+    }
+
+    //set_debt(): creates a debt, or changes one, adding the interest it is owed so far first
+    pub fn set_debt(
+        &mut self,
+        name: &str,
+        balance: Money,
+        apr: Apr,
+        minimum: Money,
+        compounding: Compounding,
+    ) -> Result<()> {
+        let name = name.to_ascii_lowercase();
+        let today = now().date();
+        self.accrue_interest(&name, today)?;
+        let debt = Debt {
+            balance,
+            apr,
+            minimum,
+            compounding,
+            accrued_through: today,
+            day: Some(today.day()),
+        };
+        self.debts.insert(name.clone(), debt.clone());
+        self.ledger.record(
+            TransactionKind::Debt {
+                name,
+                debt: Some(debt),
+            },
+            None,
+        );
+        Ok(())
+    }
+
+    pub fn remove_debt(&mut self, name: &str) -> Result<()> {
+        let name = name.to_ascii_lowercase();
+        self.debts
+            .remove(&name)
+            .ok_or(Error::DebtDoesNotExist(name.clone()))?;
+        self.ledger
+            .record(TransactionKind::Debt { name, debt: None }, None);
+        Ok(())
+    }
+
+    //pay_debt(): pays down a debt from current_balance, after adding the interest it is owed so far,
+    //paying the minimum if no amount is given, and never more than is owed
+    pub fn pay_debt(
+        &mut self,
+        name: &str,
+        cents: Option<Money>,
+        memo: Option<String>,
+        force: Force,
+    ) -> Result<String> {
+        if let Some(cents) = cents {
+            self.positive(cents)?;
         }
+        let name = name.to_ascii_lowercase();
+        if !self.debts.contains_key(&name) {
+            return Err(Error::DebtDoesNotExist(name));
+        }
+        self.accrue_interest(&name, now().date())?;
+        let debt = &self.debts[&name];
+        let cents = cents.unwrap_or(debt.minimum).min(debt.balance);
+        let cents = self.afford(&name, cents, force)?;
+
+        let balance = self.current_balance.checked_sub(cents)?;
+        let debt = self.debts.get_mut(&name).expect("debt-checked-above");
+        debt.balance = debt.balance.checked_sub(cents)?;
+        let left = debt.balance;
+        self.current_balance = balance;
+        self.ledger.record(
+            TransactionKind::DebtPayment {
+                debt: name.clone(),
+                amount: cents,
+            },
+            memo,
+        );
+
+        let c = self.notation();
+        Ok(match left > Money::ZERO {
+            true => format!(
+                "Payment made: {} to {}, {} left",
+                c.format(cents),
+                to_title_case(name),
+                c.format(left)
+            ),
+            false => format!("{} paid off!", to_title_case(name)),
+        })
     }
 
-    //save_all(): moves current_balance to savings
-    pub fn save_all(&mut self) -> Result<String> {
-        self.save(self.current_balance)
+    //accrue_interest(): adds the interest a debt is owed through the given date to its balance
+    fn accrue_interest(&mut self, name: &str, to: Date) -> Result<()> {
+        let Some(debt) = self.debts.get_mut(name) else {
+            return Ok(());
+        };
+        let (balance, through) = debt.accrued(to)?;
+        let interest = balance.checked_sub(debt.balance)?;
+        debt.balance = balance;
+        debt.accrued_through = through;
+        if interest > Money::ZERO {
+            self.ledger.record(
+                TransactionKind::Interest {
+                    debt: name.into(),
+                    amount: interest,
+                },
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    //debts(): every debt with the interest it is owed through today, sorted by name
+    pub fn debts(&self) -> Vec<(String, Debt)> {
+        let today = now().date();
+        let mut debts: Vec<(String, Debt)> = self
+            .debts
+            .iter()
+            .map(|(name, debt)| {
+                let (balance, accrued_through) = debt
+                    .accrued(today)
+                    .unwrap_or((debt.balance, debt.accrued_through));
+                let debt = Debt {
+                    balance,
+                    accrued_through,
+                    ..debt.clone()
+                };
+                (name.clone(), debt)
+            })
+            .collect();
+        debts.sort_by(|a, b| a.0.cmp(&b.0));
+        debts
+    }
+
+    pub fn total_debt(&self) -> Money {
+        self.debts().iter().map(|(_, d)| d.balance).sum()
+    }
+
+    //payoff_plans(): snowball and avalanche plans for paying off every debt from today,
+    //putting the extra amount towards them every month on top of the minimums
+    pub fn payoff_plans(&self, extra: Money) -> [PayoffPlan; 2] {
+        let debts = self.debts();
+        let today = now().date();
+        [Strategy::Snowball, Strategy::Avalanche]
+            .map(|strategy| plan_payoff(&debts, extra, strategy, today, self.notation()))
+    }
+
+    //paychecks_until(): paydays expected from today until the given date, across every income source,
+    //or monthly paychecks if there are no sources
+    pub fn paychecks_until(&self, date: Date) -> i32 {
+        let paydays = self.paydays_between(now().date(), date).len();
+        i32::try_from(paydays).unwrap_or(i32::MAX)
+    }
+
+    //paydays_between(): every payday in [from, to] with who pays and how much, counting on from
+    //each income source's past paydays, or monthly paychecks if there are no sources
+    fn paydays_between(&self, from: Date, to: Date) -> Vec<(Date, String, Money)> {
+        //paid(): every past payday from a source, oldest first
+        let paid = |source: Option<&str>| -> Vec<Date> {
+            self.ledger
+                .entries()
+                .iter()
+                .filter_map(|t| match &t.kind {
+                    TransactionKind::Income { source: s, .. } if s.as_deref() == source => {
+                        Some(t.timestamp.date())
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        if self.income_sources.is_empty() {
+            let paycheck = |date: Date| (date, String::from("paycheck"), self.expected_income);
+            return Frequency::Monthly
+                .paydays(&paid(None), from, to)
+                .into_iter()
+                .map(paycheck)
+                .collect();
+        }
+        self.income_sources()
+            .into_iter()
+            .flat_map(|(name, source)| {
+                let dates = source.frequency.paydays(&paid(Some(name)), from, to);
+                dates
+                    .into_iter()
+                    .map(move |date| (date, name.clone(), source.amount))
+            })
+            .collect()
+    }
+
+    //positive(): the given amount, or NotPositive if it is zero or negative,
+    //as moving a negative amount would move money the other way past every check
+    fn positive(&self, cents: Money) -> Result<Money> {
+        match cents > Money::ZERO {
+            true => Ok(cents),
+            false => Err(Error::NotPositive {
+                amount: cents,
+                notation: self.notation(),
+            }),
+        }
+    }
+
+    fn savings_bucket(&mut self, goal: Option<&str>) -> Result<&mut Money> {
+        match goal {
+            Some(name) => self
+                .savings_goals
+                .get_mut(name)
+                .map(|g| &mut g.saved)
+                .ok_or(Error::SavingsGoalDoesNotExist(name.into())),
+            None => Ok(&mut self.savings),
+        }
+    }
+
+    fn take_from_bucket(&mut self, goal: Option<&str>, cents: Money) -> Result<()> {
+        let saved = self.savings_bucket(goal)?;
+        if *saved < cents {
+            return Err(Error::InsufficientSavings {
+                goal: goal.unwrap_or("savings").into(),
+                amount: cents,
+                saved: *saved,
+                notation: self.notation(),
+            });
+        }
+        *saved = saved.checked_sub(cents)?;
+        Ok(())
     }
 }
-impl std::fmt::Display for Budget {
-    //fmt(): Display String has a header, with account, followed by balance and expected pay, and then all expenses
-    //TODO: better?? lol
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "==={{ Welcome, {}! }}===", self.account)?;
-        writeln!(f, "Balance: {}", format_dollars(self.current_balance))?;
-        writeln!(f, "Income: {}", format_dollars(self.expected_income))?;
-        writeln!(f, "Savings: {}", format_dollars(self.savings))?;
-        writeln!(f, "\nExpenses:")?;
+impl Budget {
+    //check(): every way this budget's data disagrees with itself, empty when it is consistent
+    pub fn check(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        //names that differ only in case, from either map
+        let mut groups: IndexMap<String, Vec<String>> = IndexMap::new();
+        for name in self.expense_names() {
+            groups
+                .entry(name.to_ascii_lowercase())
+                .or_default()
+                .push(name);
+        }
+        for (expense, names) in groups {
+            match names.as_slice() {
+                [name] if *name == expense => {}
+                [name] => findings.push(Finding::NotLowercase {
+                    expense: name.clone(),
+                }),
+                _ => findings.push(Finding::CaseDuplicates { expense, names }),
+            }
+        }
+
+        for (name, amount) in self.expected_expenses.iter() {
+            if !self.current_expenses.contains_key(name) {
+                findings.push(Finding::MissingPaid {
+                    expense: name.clone(),
+                });
+            }
+            if amount.is_negative() {
+                findings.push(Finding::NegativeExpected {
+                    expense: name.clone(),
+                    amount: *amount,
+                });
+            }
+        }
+        for (name, paid) in self.current_expenses.iter() {
+            if !self.expected_expenses.contains_key(name) {
+                findings.push(Finding::MissingExpected {
+                    expense: name.clone(),
+                    paid: *paid,
+                });
+            }
+        }
+
+        let ledger = self.ledger.balance_change();
+        if ledger != self.current_balance {
+            findings.push(Finding::BalanceMismatch {
+                balance: self.current_balance,
+                ledger,
+            });
+        }
+        findings
+    }
 
-        let mut loop_output: std::fmt::Result = Ok(());
+    //open_ledger(): gives a budget saved before it had a ledger an opening balance entry,
+    //so the ledger accounts for its balance, and check() only finds drift from then on
+    pub fn open_ledger(&mut self) {
+        if self.ledger.entries().is_empty() && self.current_balance != Money::ZERO {
+            self.ledger.record(
+                TransactionKind::OpeningBalance {
+                    amount: self.current_balance,
+                },
+                None,
+            );
+        }
+    }
 
-        for key in self.expected_expenses.iter() {
-            let current_amount = self
+    //repair(): fixes everything check() finds, returning what was fixed
+    //no money goes missing: duplicates are merged, negative expected amounts become zero,
+    //and whatever the ledger does not explain about the balance is recorded as an adjustment
+    pub fn repair(&mut self) -> Result<Vec<Finding>> {
+        let findings = self.check();
+        if findings.is_empty() {
+            return Ok(findings);
+        }
+
+        //rebuild both maps in the same order, lowercase, with every name in each,
+        //and change nothing if a merged total is out of range
+        let names = self.expense_names();
+        let mut expected: IndexMap<String, Money> = IndexMap::new();
+        let mut current: IndexMap<String, Money> = IndexMap::new();
+        let mut carried = self.period.carried.clone();
+        let mut renamed = Vec::new();
+        for name in names {
+            let lower = name.to_ascii_lowercase();
+            let amount = self
+                .expected_expenses
+                .get(&name)
+                .copied()
+                .unwrap_or_default();
+            let paid = self
                 .current_expenses
-                .get(key.0)
-                .unwrap_or_else(|| panic!("{}-missing-from-current-expenses", key.0));
-            let category_name = to_title_case(key.0.clone());
+                .get(&name)
+                .copied()
+                .unwrap_or_default();
+            let total = expected.entry(lower.clone()).or_default();
+            *total = total.checked_add(amount.max(Money::ZERO))?;
+            let total = current.entry(lower.clone()).or_default();
+            *total = total.checked_add(paid)?;
+
+            if name != lower {
+                if let Some(amount) = carried.remove(&name) {
+                    let total = carried.entry(lower.clone()).or_default();
+                    *total = total.checked_add(amount)?;
+                }
+                renamed.push((name, lower));
+            }
+        }
+
+        let ledger = self.ledger.balance_change();
+        let adjustment = self.current_balance.checked_sub(ledger)?;
+
+        self.expected_expenses = expected;
+        self.current_expenses = current;
+        self.period.carried = carried;
+        for (name, lower) in renamed {
+            if let Some(settings) = self.expense_settings.remove(&name) {
+                self.expense_settings.entry(lower).or_insert(settings);
+            }
+        }
+        if adjustment != Money::ZERO {
+            self.ledger
+                .record(TransactionKind::Adjustment { amount: adjustment }, None);
+        }
+        Ok(findings)
+    }
 
-            loop_output = writeln!(
+    //expense_names(): every name in either map, expected expenses first, in order
+    fn expense_names(&self) -> Vec<String> {
+        self.expected_expenses
+            .keys()
+            .chain(
+                self.current_expenses
+                    .keys()
+                    .filter(|name| !self.expected_expenses.contains_key(*name)),
+            )
+            .cloned()
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.report().fmt(f)
+    }
+}
+
+//Payment: the result of paying one or more expenses at once
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Payment {
+    //more than one expense when split
+    pub paid: Vec<(String, Money)>,
+    //thresholds the payment went past
+    pub alerts: Vec<Alert>,
+    pub notation: Notation,
+}
+impl Payment {
+    pub fn total(&self) -> Money {
+        self.paid.iter().map(|(_, amount)| *amount).sum()
+    }
+}
+impl std::fmt::Display for Payment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self
+            .paid
+            .iter()
+            .map(|(name, _)| to_title_case(name.clone()))
+            .collect();
+        match names.as_slice() {
+            [name] => write!(
                 f,
-                "{}: {}/{}",
-                category_name,
-                format_dollars(*current_amount),
-                format_dollars(*key.1)
+                "Payment made: {} to {name}",
+                self.notation.format(self.total())
+            ),
+            _ => write!(
+                f,
+                "Payment made: {} split between {}",
+                self.notation.format(self.total()),
+                names.join(", ")
+            ),
+        }
+    }
+}
+
+//AutomaticPayments: the result of paying every automatic expense on payday
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutomaticPayments {
+    pub paid: Vec<(String, Money)>,
+    //what is left of the paycheck afterwards
+    pub remaining: Money,
+    //thresholds the payments went past
+    pub alerts: Vec<Alert>,
+    pub notation: Notation,
+}
+impl std::fmt::Display for AutomaticPayments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.paid.is_empty() {
+            return write!(
+                f,
+                "No automatic payments due, {} left",
+                self.notation.format(self.remaining)
             );
-            if loop_output.is_err() {
-                break;
-            }
         }
+        let paid: Vec<String> = self
+            .paid
+            .iter()
+            .map(|(name, amount)| {
+                format!(
+                    "{} to {}",
+                    self.notation.format(*amount),
+                    to_title_case(name.clone())
+                )
+            })
+            .collect();
+        write!(
+            f,
+            "Automatic payments made: {}, {} left",
+            paid.join(", "),
+            self.notation.format(self.remaining)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dollars(n: i64) -> Money {
+        Money::from_cents(n * 100)
+    }
+
+    //budget(): $1,000 paid in, with $500 rent and $200 food expected
+    fn budget() -> Budget {
+        let mut bud = Budget::new("test");
+        bud.get_paid_value(dollars(1_000), None).unwrap();
+        bud.add_expense("rent", dollars(500));
+        bud.add_expense("food", dollars(200));
+        bud
+    }
+
+    #[test]
+    fn close_period_rolls_over_what_is_left() {
+        let mut bud = budget();
+        bud.set_expense_option("food", ExpenseOption::Rollover(true))
+            .unwrap();
+        bud.make_dynamic_payment("food", dollars(50), None, Force::NoConfirm)
+            .unwrap();
+        bud.make_dynamic_payment("rent", dollars(450), None, Force::NoConfirm)
+            .unwrap();
+        bud.close_period(Some("next".into())).unwrap();
+        assert!(matches!(
+            bud.close_period(Some("next".into())),
+            Err(Error::PeriodExists(_))
+        ));
+
+        let closed = &bud.archived_periods()[0];
+        let food = closed.expenses.iter().find(|e| e.name == "food").unwrap();
+        assert_eq!((food.actual, food.rolled_over), (dollars(50), dollars(150)));
+        assert_eq!(bud.current_period().name, "next");
+        assert_eq!(bud.current_expenses["food"], Money::ZERO);
+        //rent does not roll over, food's full amount is paid with what carried over
+        assert_eq!(bud.expected_for("rent"), Some(dollars(500)));
+        let payment = bud
+            .make_static_payment("Food", None, Force::NoConfirm)
+            .unwrap();
+        assert_eq!(payment.total(), dollars(350));
+        assert!(bud.check().is_empty());
+    }
+
+    #[test]
+    fn income_sources_replace_the_paycheck() {
+        let mut bud = Budget::new("test");
+        bud.set_income(dollars(3_000)).unwrap();
+        assert_eq!(bud.monthly_income(), dollars(3_000));
+        bud.set_income_source("Job", dollars(1_000), Frequency::Biweekly);
+        bud.set_income_source("side", dollars(100), Frequency::Weekly);
+        assert_eq!(bud.monthly_income(), Money::from_cents(216_666 + 43_333));
+
+        assert_eq!(
+            bud.get_paid_from("JOB", None, None).unwrap(),
+            dollars(1_000)
+        );
+        assert_eq!(
+            bud.get_paid_from("side", Some(dollars(120)), None).unwrap(),
+            dollars(120)
+        );
+        assert!(matches!(
+            bud.get_paid_from("gig", None, None),
+            Err(Error::IncomeSourceDoesNotExist(_))
+        ));
+        assert_eq!(bud.current_balance, dollars(1_120));
+    }
+
+    #[test]
+    fn paychecks_until_counts_paydays() {
+        let mut bud = Budget::new("test");
+        let today = now().date();
+        bud.set_income_source("job", dollars(500), Frequency::Weekly);
+        assert_eq!(bud.paychecks_until(add_days(today, 364)), 52);
+        bud.set_income_source("side", dollars(100), Frequency::Biweekly);
+        assert_eq!(bud.paychecks_until(add_days(today, 364)), 52 + 26);
+        assert_eq!(bud.paychecks_until(add_days(today, -7)), 0);
+    }
+
+    #[test]
+    fn goals_spread_what_is_left_over_the_paychecks_before_the_deadline() {
+        let mut bud = budget();
+        bud.set_income_source("job", dollars(500), Frequency::Weekly);
+        let deadline = add_days(now().date(), 364);
+        bud.set_savings_goal("Trip", Some(dollars(1_000)), Some(deadline), None);
+        bud.set_savings_goal("rainy day", None, None, None);
+        bud.deposit(Some("trip"), dollars(100), None).unwrap();
+
+        let goals = bud.savings_goals();
+        assert_eq!(goals[1].name, "trip");
+        assert_eq!(goals[1].paychecks_left, Some(52));
+        //$900 over 52 paychecks, rounded up so the goal is met
+        assert_eq!(goals[1].per_paycheck, Some(Money::from_cents(1_731)));
+        assert_eq!(goals[0].per_paycheck, None);
+
+        assert!(matches!(
+            bud.withdraw(Some("trip"), dollars(150), None),
+            Err(Error::InsufficientSavings { .. })
+        ));
+        bud.transfer(Some("trip"), Some("rainy day"), dollars(40))
+            .unwrap();
+        bud.remove_savings_goal("trip").unwrap();
+        assert_eq!(bud.savings, dollars(60));
+        assert_eq!(bud.total_savings(), dollars(100));
+        assert_eq!(bud.current_balance, dollars(900));
+        assert!(bud.check().is_empty());
+    }
+
+    #[test]
+    fn split_with_a_missing_expense_changes_nothing() {
+        let mut bud = budget();
+        let entries = bud.ledger.entries().len();
+        let parts = [("food".into(), dollars(50)), ("fun".into(), dollars(20))];
+        assert!(matches!(
+            bud.make_split_payment(&parts, None, Force::Overdraft),
+            Err(Error::ExpenseDoesNotExist(name)) if name == "fun"
+        ));
+        assert_eq!(bud.current_balance, dollars(1_000));
+        assert_eq!(bud.current_expenses["food"], Money::ZERO);
+        assert_eq!(bud.ledger.entries().len(), entries);
+    }
+
+    #[test]
+    fn split_past_a_limit_changes_nothing() {
+        let mut bud = budget();
+        bud.set_expense_option("food", ExpenseOption::Limit(Some("100".parse().unwrap())))
+            .unwrap();
+        let parts = [("rent".into(), dollars(500)), ("food".into(), dollars(250))];
+        assert!(matches!(
+            bud.make_split_payment(&parts, None, Force::UseRemaining),
+            Err(Error::OverLimit { .. })
+        ));
+        assert_eq!(bud.current_balance, dollars(1_000));
+        assert_eq!(bud.current_expenses["rent"], Money::ZERO);
+
+        let payment = bud
+            .make_split_payment(&parts, None, Force::Overdraft)
+            .unwrap();
+        assert_eq!(payment.total(), dollars(750));
+        assert_eq!(bud.current_balance, dollars(250));
+        assert!(bud.check().is_empty());
+    }
+
+    #[test]
+    fn amounts_must_be_positive() {
+        let mut bud = budget();
+        fn not_positive<T>(result: Result<T>) -> bool {
+            matches!(result, Err(Error::NotPositive { .. }))
+        }
+        for cents in [Money::ZERO, dollars(-5)] {
+            assert!(not_positive(bud.make_dynamic_payment(
+                "food",
+                cents,
+                None,
+                Force::Overdraft
+            )));
+            assert!(not_positive(bud.make_split_payment(
+                &[("food".into(), cents)],
+                None,
+                Force::Overdraft
+            )));
+            assert!(not_positive(bud.refund(
+                "food",
+                cents,
+                None,
+                Force::Overdraft
+            )));
+            assert!(not_positive(bud.deposit(None, cents, None)));
+            assert!(not_positive(bud.withdraw(None, cents, None)));
+            assert!(not_positive(bud.transfer(None, None, cents)));
+        }
+        assert_eq!(bud.current_balance, dollars(1_000));
+        assert!(bud.check().is_empty());
+    }
+
+    #[test]
+    fn check_finds_and_repair_fixes() {
+        let mut bud = budget();
+        bud.make_dynamic_payment("food", dollars(50), None, Force::NoConfirm)
+            .unwrap();
+        assert!(bud.check().is_empty());
+
+        bud.expected_expenses.insert("Food".into(), dollars(30));
+        bud.current_expenses.insert("Food".into(), dollars(10));
+        bud.expected_expenses.insert("gas".into(), dollars(-40));
+        bud.current_expenses.insert("fun".into(), dollars(15));
+        bud.current_balance = dollars(900);
+        let findings = bud.check();
+        assert_eq!(
+            findings,
+            vec![
+                Finding::CaseDuplicates {
+                    expense: "food".into(),
+                    names: vec!["food".into(), "Food".into()],
+                },
+                Finding::MissingPaid {
+                    expense: "gas".into()
+                },
+                Finding::NegativeExpected {
+                    expense: "gas".into(),
+                    amount: dollars(-40),
+                },
+                Finding::MissingExpected {
+                    expense: "fun".into(),
+                    paid: dollars(15),
+                },
+                Finding::BalanceMismatch {
+                    balance: dollars(900),
+                    ledger: dollars(950),
+                },
+            ]
+        );
+
+        assert_eq!(bud.repair().unwrap(), findings);
+        assert!(bud.check().is_empty());
+        assert!(bud.repair().unwrap().is_empty());
+        //nothing paid or expected goes missing, and the balance stays as it was
+        assert_eq!(bud.current_balance, dollars(900));
+        assert_eq!(bud.expected_expenses["food"], dollars(230));
+        assert_eq!(bud.current_expenses["food"], dollars(60));
+        assert_eq!(bud.expected_expenses["gas"], Money::ZERO);
+        assert_eq!(bud.expected_expenses["fun"], Money::ZERO);
+        assert_eq!(bud.current_expenses["fun"], dollars(15));
+        assert!(matches!(
+            bud.ledger.entries().last().unwrap().kind,
+            TransactionKind::Adjustment { amount } if amount == dollars(-50)
+        ));
+    }
+
+    #[test]
+    fn report_totals_what_is_left() {
+        let mut bud = budget();
+        bud.set_income(dollars(1_500)).unwrap();
+        bud.make_dynamic_payment("rent", dollars(100), None, Force::NoConfirm)
+            .unwrap();
+        bud.make_dynamic_payment("food", dollars(250), None, Force::NoConfirm)
+            .unwrap();
+
+        let report = bud.report();
+        let summary: Vec<_> = report
+            .expenses
+            .iter()
+            .map(|e| (e.name.as_str(), e.remaining, e.percent_used, e.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("rent", dollars(400), Some(20), BudgetStatus::Under),
+                ("food", dollars(-50), Some(125), BudgetStatus::Over),
+            ]
+        );
+        assert_eq!(report.total_expected, dollars(700));
+        assert_eq!(report.total_paid, dollars(350));
+        //overspending on food does not make up for rent still to pay
+        assert_eq!(report.total_remaining, dollars(400));
+        assert_eq!(report.unallocated, dollars(800));
+        assert_eq!(report.balance, dollars(650));
+        assert_eq!(report.projected_balance, dollars(250));
+
+        let mut expenses = report.expenses.clone();
+        Report::sort_expenses(&mut expenses, ExpenseSort::PercentUsed);
+        assert_eq!(expenses[0].name, "food");
+    }
+
+    #[test]
+    fn expenses_keep_the_order_they_are_moved_to() {
+        let mut bud = budget();
+        bud.add_expense("gas", dollars(60));
+        let order = |bud: &Budget| -> Vec<String> {
+            bud.report().expenses.into_iter().map(|e| e.name).collect()
+        };
+        assert_eq!(order(&bud), vec!["rent", "food", "gas"]);
+
+        let up = Position::from_args(&["up"]).unwrap();
+        assert_eq!(bud.move_expense("Gas", up).unwrap(), 2);
+        assert_eq!(order(&bud), vec!["rent", "gas", "food"]);
+        assert_eq!(bud.move_expense("rent", Position::Bottom).unwrap(), 3);
+        let far = Position::from_args(&["9"]).unwrap();
+        assert_eq!(bud.move_expense("gas", far).unwrap(), 3);
+        assert_eq!(order(&bud), vec!["food", "rent", "gas"]);
+        //paid amounts follow the same order
+        let paid: Vec<&String> = bud.current_expenses.keys().collect();
+        assert_eq!(paid, vec!["food", "rent", "gas"]);
+
+        assert!(bud.move_expense("fun", Position::Top).is_err());
+        assert!(Position::from_args(&["0"]).is_err());
+        assert!(Position::from_args(&["top", "2"]).is_err());
+    }
+
+    #[test]
+    fn tags_total_this_periods_memos() {
+        let mut bud = budget();
+        let memo = |s: &str| Some(String::from(s));
+        bud.make_dynamic_payment(
+            "food",
+            dollars(40),
+            memo("lunch #Work #reimbursable"),
+            Force::NoConfirm,
+        )
+        .unwrap();
+        bud.make_dynamic_payment(
+            "food",
+            dollars(10),
+            memo("#work coffee, #work"),
+            Force::NoConfirm,
+        )
+        .unwrap();
+        bud.refund("food", dollars(5), memo("#work"), Force::NoConfirm)
+            .unwrap();
+        //#4 is a ledger id, not a tag
+        bud.make_dynamic_payment("rent", dollars(500), memo("see #4"), Force::NoConfirm)
+            .unwrap();
+        bud.get_paid_value(dollars(40), memo("paid back #reimbursable"))
+            .unwrap();
+
+        let totals = bud.tag_totals();
+        let summary: Vec<_> = totals
+            .iter()
+            .map(|t| (t.tag.as_str(), t.spent, t.received, t.entries))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("reimbursable", dollars(40), dollars(40), 2),
+                ("work", dollars(45), Money::ZERO, 3),
+            ]
+        );
+        assert_eq!(totals[0].outstanding(), Money::ZERO);
+        let tagged: Vec<u64> = bud
+            .ledger
+            .entries()
+            .iter()
+            .filter(|t| t.has_tag("#WORK"))
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(tagged.len(), 3);
+
+        //a new period starts without any
+        bud.close_period(Some("next".into())).unwrap();
+        assert!(bud.tag_totals().is_empty());
+    }
+
+    #[test]
+    fn fifty_thirty_twenty_splits_the_paycheck() {
+        let template = Template::builtin("50-30-20").unwrap();
+        let mut bud = Budget::from_template("test", &template).unwrap();
+        bud.set_income(dollars(4_000)).unwrap();
+        assert_eq!(bud.expected_for("rent"), Some(dollars(1_200)));
+        assert_eq!(bud.expected_for("subscriptions"), Some(dollars(200)));
+        let needs: Money = ["rent", "utilities", "groceries", "transportation"]
+            .iter()
+            .filter_map(|name| bud.expected_for(name))
+            .sum();
+        assert_eq!(needs, dollars(2_000));
+
+        let report = bud.report();
+        assert_eq!(report.expenses[0].name, "rent");
+        assert_eq!(report.total_expected, dollars(3_200));
+        //the last 20% goes to savings, so nothing is left unallocated
+        assert_eq!(report.unallocated, Money::ZERO);
+        bud.get_paid_value(dollars(4_000), None).unwrap();
+        assert_eq!(
+            bud.save_shares(dollars(4_000)).unwrap(),
+            vec![("emergency".to_string(), dollars(800))]
+        );
+
+        //an account saved as a template starts the next one the same way
+        let saved = Template::from_json(&bud.template("mine").json().unwrap()).unwrap();
+        let copy = Budget::from_template("copy", &saved).unwrap();
+        assert_eq!(copy.expected_expenses, bud.expected_expenses);
+        assert_eq!(copy.expense_settings, bud.expense_settings);
+    }
+
+    #[test]
+    fn shares_follow_the_income_they_are_of() {
+        let mut bud = budget();
+        let share = |args: &[&str]| {
+            let share = crate::expense::IncomeShare::from_args(args).unwrap();
+            ExpenseOption::Share(Some(share))
+        };
+        bud.set_income(dollars(2_000)).unwrap();
+        bud.set_expense_option("rent", share(&["30"])).unwrap();
+        bud.set_expense_option("food", share(&["10", "received"]))
+            .unwrap();
+        assert_eq!(bud.expected_for("rent"), Some(dollars(600)));
+        //$1,000 received so far this period
+        assert_eq!(bud.expected_for("food"), Some(dollars(100)));
+
+        bud.set_income(dollars(3_000)).unwrap();
+        assert_eq!(bud.expected_for("rent"), Some(dollars(900)));
+        bud.add_income(dollars(500)).unwrap();
+        assert_eq!(bud.expected_for("rent"), Some(dollars(1_050)));
+        bud.get_paid_value(dollars(1_500), None).unwrap();
+        assert_eq!(bud.expected_for("food"), Some(dollars(250)));
+
+        //back to a fixed amount, which a raise leaves alone
+        bud.set_expense_option("rent", ExpenseOption::Share(None))
+            .unwrap();
+        bud.add_income(dollars(500)).unwrap();
+        assert_eq!(bud.expected_for("rent"), Some(dollars(1_050)));
+        //nothing is received yet in a new period
+        bud.close_period(Some("next".into())).unwrap();
+        assert_eq!(bud.expected_for("food"), Some(Money::ZERO));
+    }
+
+    #[test]
+    fn stored_totals_never_overflow() {
+        let mut bud = budget();
+        let max = Money::from_cents(i64::MAX);
+        bud.expected_expenses.insert("Food".into(), max);
+        assert!(matches!(bud.repair(), Err(Error::MoneyOverflow)));
+        //nothing was merged
+        assert_eq!(bud.expected_expenses["Food"], max);
+        assert!(!bud.check().is_empty());
+        bud.expected_expenses.shift_remove("Food");
+
+        let share = crate::expense::IncomeShare::from_args(&["200"]).unwrap();
+        bud.set_expense_option("food", ExpenseOption::Share(Some(share)))
+            .unwrap();
+        let income = bud.expected_income;
+        assert!(matches!(bud.set_income(max), Err(Error::MoneyOverflow)));
+        assert_eq!(bud.expected_income, income);
+
+        bud.period.carried.insert("rent".into(), max);
+        assert!(matches!(bud.close_period(None), Err(Error::MoneyOverflow)));
+        assert!(bud.archived_periods().is_empty());
+    }
 
-        loop_output
+    #[test]
+    fn open_ledger_accounts_for_an_older_balance() {
+        let mut bud = budget();
+        bud.ledger = Ledger::default();
+        assert_eq!(bud.check().len(), 1);
+        bud.open_ledger();
+        assert!(bud.check().is_empty());
+        //only ever once
+        bud.open_ledger();
+        assert_eq!(bud.ledger.entries().len(), 1);
     }
 }
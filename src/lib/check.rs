@@ -0,0 +1,67 @@
+use crate::locale::{InNotation, Notation};
+use crate::money::Money;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+
+//Finding: one way a Budget's data disagrees with itself, as found by Budget::check()
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Finding {
+    //an expected amount with nothing tracking what was paid
+    MissingPaid { expense: String },
+    //a paid amount with no expected amount
+    MissingExpected { expense: String, paid: Money },
+    //a name that cannot be reached, as every lookup is lowercase
+    NotLowercase { expense: String },
+    //names that differ only in case, which show up as the same expense
+    CaseDuplicates { expense: String, names: Vec<String> },
+    NegativeExpected { expense: String, amount: Money },
+    //the balance does not match the net of every ledger entry
+    BalanceMismatch { balance: Money, ledger: Money },
+}
+
+impl std::fmt::Display for InNotation<'_, Finding> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let InNotation(finding, c) = self;
+        match finding {
+            Finding::MissingPaid { expense } => {
+                write!(f, "{expense:?} has an expected amount but no paid amount")
+            }
+            Finding::MissingExpected { expense, paid } => write!(
+                f,
+                "{expense:?} has {} paid but no expected amount",
+                c.format(*paid)
+            ),
+            Finding::NotLowercase { expense } => {
+                write!(f, "{expense:?} is not lowercase and cannot be looked up")
+            }
+            Finding::CaseDuplicates { names, .. } => write!(
+                f,
+                "{} differ only in case",
+                names
+                    .iter()
+                    .map(|n| format!("{n:?}"))
+                    .collect::<Vec<String>>()
+                    .join(" and ")
+            ),
+            Finding::NegativeExpected { expense, amount } => write!(
+                f,
+                "{} expects a negative amount, {}",
+                to_title_case(expense.clone()),
+                c.format(*amount)
+            ),
+            Finding::BalanceMismatch { balance, ledger } => write!(
+                f,
+                "balance is {} but the ledger adds up to {}",
+                c.format(*balance),
+                c.format(*ledger)
+            ),
+        }
+    }
+}
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Notation::default().show(self).fmt(f)
+    }
+}
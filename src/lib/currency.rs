@@ -0,0 +1,284 @@
+use crate::error::{Error, Result};
+use crate::locale::Notation;
+use crate::money::Money;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+//CURRENCIES: code, symbol and minor units (decimal places) of every supported currency
+const CURRENCIES: &[(&str, &str, u8)] = &[
+    ("USD", "$", 2),
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("JPY", "¥", 0),
+    ("CNY", "CN¥", 2),
+    ("KRW", "₩", 0),
+    ("INR", "₹", 2),
+    ("CAD", "CA$", 2),
+    ("AUD", "A$", 2),
+    ("NZD", "NZ$", 2),
+    ("MXN", "MX$", 2),
+    ("BRL", "R$", 2),
+    ("CHF", "CHF ", 2),
+    ("SEK", "SEK ", 2),
+    ("NOK", "NOK ", 2),
+    ("DKK", "DKK ", 2),
+    ("PLN", "zł ", 2),
+    ("ISK", "ISK ", 0),
+    ("KWD", "KD ", 3),
+    ("BHD", "BD ", 3),
+    ("OMR", "OMR ", 3),
+    ("JOD", "JD ", 3),
+    ("TND", "DT ", 3),
+];
+
+//Currency: an ISO 4217 currency, saved as its code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Currency {
+    code: &'static str,
+    symbol: &'static str,
+    minor_units: u8,
+}
+impl Currency {
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        self.symbol.trim_end()
+    }
+
+    //minor_units(): how many decimal places amounts have, 2 for cents, 0 for JPY, 3 for KWD
+    pub fn minor_units(&self) -> u8 {
+        self.minor_units
+    }
+
+    //prefix(): the symbol as written before a number, with a space after it if it is made of letters
+    pub(crate) fn prefix(&self) -> &'static str {
+        self.symbol
+    }
+
+    //format(): an amount of minor units with the symbol and the right number of decimal places
+    pub fn format(&self, amount: Money) -> String {
+        Notation::from(*self).format(amount)
+    }
+
+    //parse(): an amount in this currency, with its symbol or code, into minor units
+    pub fn parse(&self, s: &str) -> Result<Money> {
+        Notation::from(*self).parse(s)
+    }
+}
+impl Default for Currency {
+    fn default() -> Self {
+        "USD".parse().expect("usd-is-supported")
+    }
+}
+impl std::str::FromStr for Currency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        CURRENCIES
+            .iter()
+            .find(|(code, _, _)| code.eq_ignore_ascii_case(s))
+            .map(|&(code, symbol, minor_units)| Currency {
+                code,
+                symbol,
+                minor_units,
+            })
+            .ok_or_else(|| Error::InvalidCurrency(s.into()))
+    }
+}
+impl Serialize for Currency {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code)
+    }
+}
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+impl std::fmt::Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.code)
+    }
+}
+
+//Rate: an exact decimal exchange rate, mantissa / 10^scale
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rate {
+    mantissa: i128,
+    scale: u32,
+}
+impl std::str::FromStr for Rate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidExchangeRate(s.into());
+        let (whole, fraction) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+        if whole.is_empty() && fraction.is_empty()
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let mantissa: i128 = format!("{whole}{fraction}")
+            .parse()
+            .map_err(|_| invalid())?;
+        if mantissa == 0 {
+            return Err(invalid());
+        }
+        Ok(Rate {
+            mantissa,
+            scale: fraction.len() as u32,
+        })
+    }
+}
+
+//RateValue: rates may be written as JSON numbers or strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RateValue {
+    Number(serde_json::Number),
+    Text(String),
+}
+
+//ExchangeRates: a user-maintained table of how much one unit of the base currency buys in others
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(try_from = "RateTable")]
+pub struct ExchangeRates {
+    base: Currency,
+    rates: HashMap<Currency, Rate>,
+}
+#[derive(Deserialize)]
+struct RateTable {
+    base: String,
+    rates: HashMap<String, RateValue>,
+}
+impl TryFrom<RateTable> for ExchangeRates {
+    type Error = Error;
+
+    fn try_from(table: RateTable) -> Result<Self> {
+        let mut rates = HashMap::new();
+        for (code, value) in table.rates {
+            let rate = match value {
+                RateValue::Number(n) => n.to_string(),
+                RateValue::Text(s) => s,
+            };
+            rates.insert(code.parse()?, rate.parse()?);
+        }
+        Ok(ExchangeRates {
+            base: table.base.parse()?,
+            rates,
+        })
+    }
+}
+impl ExchangeRates {
+    //from_json(): reads a table like {"base": "USD", "rates": {"EUR": "0.92", "JPY": 151.3}}
+    pub fn from_json(json: &str) -> Result<ExchangeRates> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn base(&self) -> Currency {
+        self.base
+    }
+
+    //rate(): units of the currency per unit of the base currency
+    fn rate(&self, currency: Currency) -> Result<Rate> {
+        if currency == self.base {
+            return Ok(Rate {
+                mantissa: 1,
+                scale: 0,
+            });
+        }
+        self.rates
+            .get(&currency)
+            .copied()
+            .ok_or(Error::MissingExchangeRate(currency.code.into()))
+    }
+
+    //convert(): an amount in one currency into another, rounded half away from zero to its minor units
+    pub fn convert(&self, amount: Money, from: Currency, to: Currency) -> Result<Money> {
+        if from == to {
+            return Ok(amount);
+        }
+        let (from_rate, to_rate) = (self.rate(from)?, self.rate(to)?);
+        let pow = |n: u32| 10i128.checked_pow(n).ok_or(Error::MoneyOverflow);
+        let (from_scale, to_scale) = (pow(from_rate.scale)?, pow(to_rate.scale)?);
+        let (from_minor, to_minor) = (pow(from.minor_units as u32)?, pow(to.minor_units as u32)?);
+        //amount * to/from, moved from the minor units of one currency to the other
+        let numerator = [to_rate.mantissa, from_scale, to_minor]
+            .into_iter()
+            .try_fold(amount.cents() as i128, i128::checked_mul)
+            .ok_or(Error::MoneyOverflow)?;
+        let denominator = [to_scale, from_minor]
+            .into_iter()
+            .try_fold(from_rate.mantissa, i128::checked_mul)
+            .ok_or(Error::MoneyOverflow)?;
+        let quotient = numerator / denominator;
+        let rounding = match (numerator % denominator).abs() * 2 >= denominator {
+            true => numerator.signum(),
+            false => 0,
+        };
+        i64::try_from(quotient + rounding)
+            .map(Money::from_cents)
+            .map_err(|_| Error::MoneyOverflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> ExchangeRates {
+        ExchangeRates::from_json(r#"{"base": "USD", "rates": {"JPY": 150, "KWD": "0.307"}}"#)
+            .unwrap()
+    }
+
+    fn convert(cents: i64, from: &str, to: &str) -> Result<i64> {
+        let (from, to) = (from.parse().unwrap(), to.parse().unwrap());
+        Ok(rates().convert(Money::from_cents(cents), from, to)?.cents())
+    }
+
+    #[test]
+    fn convert_moves_between_minor_units() {
+        //$10.00 is ¥1500 and KD 3.070
+        assert_eq!(convert(1_000, "USD", "JPY").unwrap(), 1_500);
+        assert_eq!(convert(1_000, "USD", "KWD").unwrap(), 3_070);
+        assert_eq!(convert(1_500, "JPY", "USD").unwrap(), 1_000);
+        assert_eq!(convert(3_070, "KWD", "JPY").unwrap(), 1_500);
+        assert_eq!(convert(-1_000, "USD", "JPY").unwrap(), -1_500);
+        assert_eq!(convert(-3_070, "KWD", "USD").unwrap(), -1_000);
+        assert_eq!(convert(42, "JPY", "JPY").unwrap(), 42);
+        assert!(matches!(
+            convert(1_000, "USD", "EUR"),
+            Err(Error::MissingExchangeRate(_))
+        ));
+    }
+
+    #[test]
+    fn convert_rounds_half_away_from_zero() {
+        //1 cent is ¥1.5
+        assert_eq!(convert(1, "USD", "JPY").unwrap(), 2);
+        assert_eq!(convert(-1, "USD", "JPY").unwrap(), -2);
+        //1 cent is 3.07 fils
+        assert_eq!(convert(1, "USD", "KWD").unwrap(), 3);
+        assert_eq!(convert(-1, "USD", "KWD").unwrap(), -3);
+        //¥1 is 0.67 cents
+        assert_eq!(convert(1, "JPY", "USD").unwrap(), 1);
+        assert_eq!(convert(-1, "JPY", "USD").unwrap(), -1);
+        assert!(matches!(
+            convert(i64::MAX, "USD", "JPY"),
+            Err(Error::MoneyOverflow)
+        ));
+    }
+}
@@ -0,0 +1,430 @@
+use crate::error::{Error, Result};
+use crate::locale::{InNotation, Notation};
+use crate::money::Money;
+use crate::schedule::day_in_month;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+//PLANNER_MONTHS: how far ahead a payoff plan looks before calling a debt never paid off
+const PLANNER_MONTHS: u32 = 1200;
+
+//Apr: an annual percentage rate, exact to a thousandth of a percent
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Apr(u32);
+impl Apr {
+    //from_thousandths(): 19990 is 19.99%
+    pub const fn from_thousandths(thousandths: u32) -> Apr {
+        Apr(thousandths)
+    }
+
+    pub const fn thousandths(self) -> u32 {
+        self.0
+    }
+
+    //interest(): one compounding period's interest on a balance, rounded half away from zero
+    fn interest(self, balance: Money, periods_per_year: i128) -> Money {
+        if balance <= Money::ZERO {
+            return Money::ZERO;
+        }
+        let numerator = balance.cents() as i128 * self.0 as i128;
+        let denominator = 100_000 * periods_per_year;
+        let interest = (numerator + denominator / 2) / denominator;
+        Money::from_cents(interest.min(i64::MAX as i128) as i64)
+    }
+}
+impl std::str::FromStr for Apr {
+    type Err = Error;
+
+    //from_str(): "19.99" or "19.99%"
+    fn from_str(s: &str) -> Result<Self> {
+        parse_amount(s, &["%"], 3, '.', ',')
+            .ok()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Apr)
+            .ok_or_else(|| Error::InvalidApr(s.into()))
+    }
+}
+impl std::fmt::Display for Apr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fraction = format!("{:03}", self.0 % 1000);
+        match fraction.trim_end_matches('0') {
+            "" => write!(f, "{}%", self.0 / 1000),
+            fraction => write!(f, "{}.{fraction}%", self.0 / 1000),
+        }
+    }
+}
+
+//Compounding: how often unpaid interest is added to a debt
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compounding {
+    //car loans, mortgages: once a month, on the day the debt was added
+    #[default]
+    Monthly,
+    //credit cards: every day
+    Daily,
+}
+impl std::str::FromStr for Compounding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "monthly" => Ok(Compounding::Monthly),
+            "daily" => Ok(Compounding::Daily),
+            _ => Err(Error::InvalidCompounding(s.into())),
+        }
+    }
+}
+impl std::fmt::Display for Compounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compounding::Monthly => write!(f, "monthly"),
+            Compounding::Daily => write!(f, "daily"),
+        }
+    }
+}
+
+//Debt: a loan or card balance that grows with interest until it is paid down
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Debt {
+    //what is owed, including interest already added
+    pub balance: Money,
+    pub apr: Apr,
+    //monthly minimum payment
+    pub minimum: Money,
+    pub compounding: Compounding,
+    //interest has been added up to this date
+    #[serde(with = "date_format")]
+    pub accrued_through: Date,
+    //the day of the month monthly interest is added, the day the debt was added,
+    //None in saves from before it was kept, which go by accrued_through
+    #[serde(default)]
+    pub day: Option<u8>,
+}
+impl Debt {
+    //accrued(): the balance with interest added through the given date, and the date it is accrued through,
+    //which for monthly compounding is the last full month, or MoneyOverflow if the balance grows out of range
+    pub fn accrued(&self, to: Date) -> Result<(Money, Date)> {
+        let mut balance = self.balance;
+        let mut through = self.accrued_through;
+        match self.compounding {
+            Compounding::Monthly => {
+                let day = self.day.unwrap_or(through.day());
+                while let Some(next) = on_day(through, 1, day).filter(|next| *next <= to) {
+                    balance = balance.checked_add(self.apr.interest(balance, 12))?;
+                    through = next;
+                }
+            }
+            Compounding::Daily => {
+                while through < to {
+                    balance = balance.checked_add(self.apr.interest(balance, 365))?;
+                    through = through.next_day().unwrap_or(to);
+                }
+            }
+        }
+        Ok((balance, through))
+    }
+
+    //month_interest(): interest the balance gathers over the month starting at the given date
+    fn month_interest(&self, balance: Money, from: Date) -> Money {
+        let debt = Debt {
+            balance,
+            accrued_through: from,
+            day: None,
+            ..self.clone()
+        };
+        let to = add_months(from, 1).unwrap_or(from);
+        match debt.accrued(to) {
+            Ok((accrued, _)) => accrued.saturating_sub(balance),
+            Err(_) => Money::from_cents(i64::MAX).saturating_sub(balance),
+        }
+    }
+}
+impl std::fmt::Display for InNotation<'_, Debt> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let InNotation(debt, c) = self;
+        write!(
+            f,
+            "{} at {} ({}), {} minimum",
+            c.format(debt.balance),
+            debt.apr,
+            debt.compounding,
+            c.format(debt.minimum)
+        )
+    }
+}
+
+//add_months(): the same day of a later month, or its last day if the month is shorter
+fn add_months(date: Date, months: u32) -> Option<Date> {
+    on_day(date, months, date.day())
+}
+
+//on_day(): the given day of a later month, or its last day if the month is shorter
+fn on_day(date: Date, months: u32, day: u8) -> Option<Date> {
+    let index = date.year() as i64 * 12 + date.month() as i64 - 1 + months as i64;
+    let year = i32::try_from(index.div_euclid(12)).ok()?;
+    let month = time::Month::try_from((index.rem_euclid(12) + 1) as u8).ok()?;
+    day_in_month(year, month, day)
+}
+
+//Strategy: which debt gets money beyond the minimums first
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    //smallest balance first
+    Snowball,
+    //highest APR first
+    Avalanche,
+}
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strategy::Snowball => write!(f, "snowball"),
+            Strategy::Avalanche => write!(f, "avalanche"),
+        }
+    }
+}
+
+//PaidOff: when one debt in a payoff plan is paid off
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PaidOff {
+    pub name: String,
+    #[serde(with = "date_format")]
+    pub date: Date,
+}
+
+//PayoffPlan: paying every debt down month by month with one strategy
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PayoffPlan {
+    pub strategy: Strategy,
+    //what goes to debts every month, the minimums plus the extra amount
+    pub monthly: Money,
+    //None if the debts are not paid off within PLANNER_MONTHS
+    pub months: Option<u32>,
+    #[serde(with = "date_format::option")]
+    pub payoff_date: Option<Date>,
+    pub total_interest: Money,
+    pub total_paid: Money,
+    //when each debt is paid off, in order
+    pub paid_off: Vec<PaidOff>,
+    pub notation: Notation,
+}
+
+impl std::fmt::Display for PayoffPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = self.notation;
+        let name = to_title_case(self.strategy.to_string());
+        match (self.months, self.payoff_date) {
+            (Some(months), Some(date)) => write!(
+                f,
+                "{name}: debt free by {date} ({months} months), {} interest, {} paid in total",
+                c.format(self.total_interest),
+                c.format(self.total_paid)
+            )?,
+            _ => write!(
+                f,
+                "{name}: never paid off, {} a month does not outpace the interest",
+                c.format(self.monthly)
+            )?,
+        }
+        if !self.paid_off.is_empty() {
+            let order: Vec<String> = self
+                .paid_off
+                .iter()
+                .map(|p| format!("{} {}", to_title_case(p.name.clone()), p.date))
+                .collect();
+            write!(f, "\n\tpaid off: {}", order.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+//plan_payoff(): simulates paying every minimum each month from `start`, with the extra amount and any minimums
+//freed up by paid off debts going to the debt the strategy picks
+pub fn plan_payoff(
+    debts: &[(String, Debt)],
+    extra: Money,
+    strategy: Strategy,
+    start: Date,
+    notation: Notation,
+) -> PayoffPlan {
+    let monthly = debts
+        .iter()
+        .map(|(_, d)| d.minimum)
+        .sum::<Money>()
+        .saturating_add(extra);
+    let mut balances: Vec<Money> = debts.iter().map(|(_, d)| d.balance).collect();
+    let mut order: Vec<usize> = (0..debts.len()).collect();
+    match strategy {
+        Strategy::Snowball => {
+            order.sort_by_key(|&i| (balances[i], std::cmp::Reverse(debts[i].1.apr)))
+        }
+        Strategy::Avalanche => {
+            order.sort_by_key(|&i| (std::cmp::Reverse(debts[i].1.apr), balances[i]))
+        }
+    }
+
+    let mut plan = PayoffPlan {
+        strategy,
+        monthly,
+        months: None,
+        payoff_date: None,
+        total_interest: Money::ZERO,
+        total_paid: Money::ZERO,
+        paid_off: Vec::new(),
+        notation,
+    };
+    if balances.iter().all(|b| *b <= Money::ZERO) {
+        plan.months = Some(0);
+        plan.payoff_date = Some(start);
+        return plan;
+    }
+
+    for month in 0..PLANNER_MONTHS {
+        let Some(from) = add_months(start, month) else {
+            break;
+        };
+        let to = add_months(from, 1).unwrap_or(from);
+        for (i, (_, debt)) in debts.iter().enumerate() {
+            let interest = debt.month_interest(balances[i], from);
+            balances[i] = balances[i].saturating_add(interest);
+            plan.total_interest = plan.total_interest.saturating_add(interest);
+        }
+
+        let mut budget = monthly;
+        let mut pay = |i: usize, amount: Money, budget: &mut Money| {
+            let amount = amount.min(balances[i]).min(*budget).max(Money::ZERO);
+            balances[i] = balances[i].saturating_sub(amount);
+            *budget = budget.saturating_sub(amount);
+            amount
+        };
+        let mut paid = Money::ZERO;
+        for (i, (_, debt)) in debts.iter().enumerate() {
+            paid = paid.saturating_add(pay(i, debt.minimum, &mut budget));
+        }
+        for &i in order.iter() {
+            paid = paid.saturating_add(pay(i, budget, &mut budget));
+        }
+        plan.total_paid = plan.total_paid.saturating_add(paid);
+
+        for (i, (name, debt)) in debts.iter().enumerate() {
+            let owed = debt.balance > Money::ZERO;
+            if owed && balances[i] <= Money::ZERO && !plan.paid_off.iter().any(|p| p.name == *name)
+            {
+                plan.paid_off.push(PaidOff {
+                    name: name.clone(),
+                    date: to,
+                });
+            }
+        }
+        if balances.iter().all(|b| *b <= Money::ZERO) {
+            plan.months = Some(month + 1);
+            plan.payoff_date = Some(to);
+            break;
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dollars(n: i64) -> Money {
+        Money::from_cents(n * 100)
+    }
+
+    fn date(s: &str) -> Date {
+        parse_date(s).unwrap()
+    }
+
+    fn debt(balance: i64, apr: &str, minimum: i64) -> Debt {
+        Debt {
+            balance: dollars(balance),
+            apr: apr.parse().unwrap(),
+            minimum: dollars(minimum),
+            compounding: Compounding::Monthly,
+            accrued_through: date("2025-01-01"),
+            day: None,
+        }
+    }
+
+    #[test]
+    fn monthly_interest_keeps_the_day_the_debt_was_added() {
+        let mut loan = debt(1_000, "12", 50);
+        loan.accrued_through = date("2025-01-31");
+        loan.day = Some(31);
+        //1% a month, added on the last day of short months
+        let (balance, through) = loan.accrued(date("2025-03-30")).unwrap();
+        assert_eq!((balance, through), (dollars(1_010), date("2025-02-28")));
+        loan.balance = balance;
+        loan.accrued_through = through;
+        let (balance, through) = loan.accrued(date("2025-03-31")).unwrap();
+        assert_eq!(
+            (balance, through),
+            (Money::from_cents(102_010), date("2025-03-31"))
+        );
+    }
+
+    #[test]
+    fn plan_payoff_counts_months_and_interest() {
+        let debts = vec![("loan".to_string(), debt(100, "12", 60))];
+        let plan = plan_payoff(
+            &debts,
+            Money::ZERO,
+            Strategy::Snowball,
+            date("2025-01-01"),
+            Notation::default(),
+        );
+        //$1.00 interest, $60 paid, then $0.41 interest and the last $41.41
+        assert_eq!(plan.months, Some(2));
+        assert_eq!(plan.payoff_date, Some(date("2025-03-01")));
+        assert_eq!(plan.total_interest, Money::from_cents(141));
+        assert_eq!(plan.total_paid, Money::from_cents(10_141));
+        assert_eq!(plan.paid_off[0].date, date("2025-03-01"));
+
+        let never = vec![("loan".to_string(), debt(10_000, "24", 100))];
+        let plan = plan_payoff(
+            &never,
+            Money::ZERO,
+            Strategy::Avalanche,
+            date("2025-01-01"),
+            Notation::default(),
+        );
+        assert_eq!((plan.months, plan.payoff_date), (None, None));
+    }
+
+    #[test]
+    fn strategies_pick_the_order() {
+        let debts = vec![
+            ("card".to_string(), debt(1_000, "24", 30)),
+            ("car".to_string(), debt(500, "6", 30)),
+        ];
+        let plan = |strategy| {
+            plan_payoff(
+                &debts,
+                dollars(100),
+                strategy,
+                date("2025-01-01"),
+                Notation::default(),
+            )
+        };
+        let (snowball, avalanche) = (plan(Strategy::Snowball), plan(Strategy::Avalanche));
+        let order = |plan: &PayoffPlan| -> Vec<String> {
+            plan.paid_off.iter().map(|p| p.name.clone()).collect()
+        };
+        assert_eq!(order(&snowball), vec!["car", "card"]);
+        assert_eq!(order(&avalanche), vec!["card", "car"]);
+        assert!(avalanche.total_interest < snowball.total_interest);
+        for plan in [snowball, avalanche] {
+            assert!(plan.months.is_some());
+            assert_eq!(plan.monthly, dollars(160));
+            assert_eq!(
+                plan.total_paid,
+                dollars(1_500).checked_add(plan.total_interest).unwrap()
+            );
+        }
+    }
+}
@@ -1,22 +1,148 @@
+use crate::currency::Currency;
+use crate::locale::Notation;
+use crate::money::{Money, Percent};
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[error("dollar value error: {0:?}")]
-    InvalidDollarValue(String),
+    #[error("dollar value error: {value:?} at character {position}: {reason}")]
+    InvalidDollarValue {
+        value: String,
+        position: usize,
+        reason: String,
+    },
+
+    #[error("money error: amount is out of range")]
+    MoneyOverflow,
 
     #[error(
-        "budget account error: cannot afford {expense} ({amount}) with balance {remaining_balance}"
+        "budget account error: cannot afford {expense} ({}) with balance {}",
+        .notation.format(*.amount),
+        .notation.format(*.remaining_balance)
     )]
     CannotAfford {
         expense: String,
-        amount: i32,
-        remaining_balance: i32,
+        amount: Money,
+        remaining_balance: Money,
+        notation: Notation,
+    },
+
+    #[error(
+        "budget account error: amounts must be more than zero, not {}",
+        .notation.format(*.amount)
+    )]
+    NotPositive { amount: Money, notation: Notation },
+
+    #[error(
+        "budget account error: paying {} to {expense} goes past its {limit} limit, {} is left under it",
+        .notation.format(*.amount),
+        .notation.format(*.allowed)
+    )]
+    OverLimit {
+        expense: String,
+        amount: Money,
+        limit: Percent,
+        allowed: Money,
+        notation: Notation,
     },
 
+    #[error("currency error: {0:?} is not a supported currency code")]
+    InvalidCurrency(String),
+    #[error("budget account error: {0:?} is not a valid percentage, like 12.5%")]
+    InvalidPercent(String),
+    #[error("debt error: {0:?} is not a valid APR, like 19.99%")]
+    InvalidApr(String),
+    #[error("debt error: {0:?} is not monthly or daily compounding")]
+    InvalidCompounding(String),
+    #[error("locale error: {0:?} is not a supported locale")]
+    InvalidLocale(String),
+
+    #[error("currency error: cannot switch from {from} to {to} once amounts are recorded, they have different decimal places")]
+    CurrencyChange { from: Currency, to: Currency },
+
+    #[error("exchange rate error: {0:?} is not a positive decimal rate")]
+    InvalidExchangeRate(String),
+
+    #[error("exchange rate error: no rate for {0} in the exchange rate table")]
+    MissingExchangeRate(String),
+
+    #[error("date error: {0:?} is not a YYYY-MM-DD date")]
+    InvalidDate(String),
+
     #[error("budget account error: expense {0} does not exist")]
     ExpenseDoesNotExist(String),
+    #[error("budget account error: expense {0} already exists")]
+    ExpenseExists(String),
+
+    #[error(
+        "budget account error: cannot refund {} to {expense} with {} paid",
+        .notation.format(*.amount),
+        .notation.format(*.paid)
+    )]
+    RefundExceedsPaid {
+        expense: String,
+        amount: Money,
+        paid: Money,
+        notation: Notation,
+    },
+
+    #[error("ledger error: transaction #{0} does not exist")]
+    TransactionDoesNotExist(u64),
+    #[error("ledger error: transaction #{0} is not a payment")]
+    NotAPayment(u64),
+    #[error("ledger error: payment #{0} is already reversed")]
+    AlreadyReversed(u64),
+
+    #[error("budget account error: invalid expense option {0:?}")]
+    InvalidExpenseOption(String),
+    #[error("budget account error: {0:?} is not up, down, top, bottom or a position")]
+    InvalidPosition(String),
+    #[error("budget account error: cannot sort expenses by {0:?}, only position, name, amount, remaining or percent used")]
+    InvalidExpenseSort(String),
+
+    #[error("budget account error: savings goal {0} does not exist")]
+    SavingsGoalDoesNotExist(String),
+    #[error("budget account error: debt {0} does not exist")]
+    DebtDoesNotExist(String),
+
+    #[error(
+        "budget account error: cannot take {} from {goal} with {} saved",
+        .notation.format(*.amount),
+        .notation.format(*.saved)
+    )]
+    InsufficientSavings {
+        goal: String,
+        amount: Money,
+        saved: Money,
+        notation: Notation,
+    },
+
+    #[error("budget account error: income source {0} does not exist")]
+    IncomeSourceDoesNotExist(String),
+
+    #[error("income error: {0:?} is not weekly, biweekly, semi-monthly or monthly")]
+    InvalidFrequency(String),
+
+    #[error("schedule error: {0:?} is not a recognized recurrence")]
+    InvalidRecurrence(String),
+
+    #[error("budget period error: period {0} already exists")]
+    PeriodExists(String),
+
+    #[error("history error: nothing to undo")]
+    NothingToUndo,
+
+    #[error("history error: nothing to redo")]
+    NothingToRedo,
 
     #[error("json error: {0}")]
     JsonFailure(#[from] serde_json::Error),
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(feature = "wasm")]
+impl From<Error> for wasm_bindgen::JsValue {
+    fn from(e: Error) -> Self {
+        wasm_bindgen::JsValue::from_str(&e.to_string())
+    }
+}
@@ -0,0 +1,292 @@
+use crate::error::{Error, Result};
+use crate::locale::{InNotation, Notation};
+use crate::money::{Money, Percent};
+use crate::schedule::Recurrence;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+
+//ExpenseSettings: optional per-expense behaviour, expenses without an entry use the defaults
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ExpenseSettings {
+    //carry unspent amounts over into the next period
+    #[serde(default)]
+    pub rollover: bool,
+    //whatever is still unpaid gets paid on payday, when automatic payments are enabled for the account
+    #[serde(default)]
+    pub automatic: bool,
+    //when the expense comes due
+    #[serde(default)]
+    pub due: Option<Recurrence>,
+    //None = a fixed amount, otherwise the expected amount follows income
+    #[serde(default)]
+    pub share: Option<IncomeShare>,
+    //warn once payments reach this much of the expected amount
+    #[serde(default)]
+    pub warn_at: Option<Percent>,
+    //refuse payments past this much of the expected amount, unless forced
+    #[serde(default)]
+    pub limit_at: Option<Percent>,
+}
+
+//IncomeShare: an expense expected to take a percentage of income, like 10% to giving
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncomeShare {
+    pub percent: Percent,
+    pub basis: ShareBasis,
+}
+impl IncomeShare {
+    //from_args(): a percentage, then optionally "paycheck" (the default) or "received"
+    pub fn from_args(values: &[&str]) -> Result<IncomeShare> {
+        let percent = values
+            .first()
+            .ok_or(Error::InvalidPercent(String::new()))?
+            .parse()?;
+        let basis = match values.get(1).map(|v| v.to_ascii_lowercase()).as_deref() {
+            None | Some("paycheck") | Some("expected") => ShareBasis::Paycheck,
+            Some("received") | Some("actual") => ShareBasis::Received,
+            Some(other) => return Err(Error::InvalidExpenseOption(other.into())),
+        };
+        Ok(IncomeShare { percent, basis })
+    }
+}
+impl std::fmt::Display for IncomeShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of {}", self.percent, self.basis)
+    }
+}
+
+//ShareBasis: which income an IncomeShare is a percentage of
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareBasis {
+    //the expected paycheck
+    #[default]
+    Paycheck,
+    //income actually received this period
+    Received,
+}
+impl std::fmt::Display for ShareBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareBasis::Paycheck => write!(f, "paycheck"),
+            ShareBasis::Received => write!(f, "income received"),
+        }
+    }
+}
+
+//ExpenseOption: a single change to an expense's settings
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpenseOption {
+    Rollover(bool),
+    Automatic(bool),
+    //None = no due date
+    Due(Option<Recurrence>),
+    //None = back to a fixed amount
+    Share(Option<IncomeShare>),
+    //None = no warning
+    Warn(Option<Percent>),
+    //None = no limit
+    Limit(Option<Percent>),
+}
+impl ExpenseOption {
+    //from_args(): parses an option name and its value(s), e.g. "rollover" ["on"] or "due" ["monthly", "1"]
+    pub fn from_args(option: &str, values: &[&str]) -> Result<ExpenseOption> {
+        let value = values.first().copied();
+        match option.to_ascii_lowercase().as_str() {
+            "rollover" => Ok(ExpenseOption::Rollover(parse_toggle(value)?)),
+            "automatic" | "auto" => Ok(ExpenseOption::Automatic(parse_toggle(value)?)),
+            "due" => match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+                None | Some("none") | Some("off") => Ok(ExpenseOption::Due(None)),
+                Some(_) => Ok(ExpenseOption::Due(Some(Recurrence::from_args(values)?))),
+            },
+            "share" | "percent" => match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+                None | Some("none") | Some("off") => Ok(ExpenseOption::Share(None)),
+                Some(_) => Ok(ExpenseOption::Share(Some(IncomeShare::from_args(values)?))),
+            },
+            "warn" => Ok(ExpenseOption::Warn(percent_or_off(value)?)),
+            "limit" | "block" => Ok(ExpenseOption::Limit(percent_or_off(value)?)),
+            other => Err(Error::InvalidExpenseOption(other.into())),
+        }
+    }
+}
+
+//percent_or_off(): a percentage, or None for "none"/"off"
+fn percent_or_off(value: Option<&str>) -> Result<Option<Percent>> {
+    match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+        None | Some("none") | Some("off") => Ok(None),
+        Some(_) => Ok(Some(value.unwrap_or_default().parse()?)),
+    }
+}
+impl std::fmt::Display for ExpenseOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpenseOption::Rollover(enabled) => write!(f, "rollover {}", toggle_str(*enabled)),
+            ExpenseOption::Automatic(enabled) => write!(f, "automatic {}", toggle_str(*enabled)),
+            ExpenseOption::Due(Some(rule)) => write!(f, "due {rule}"),
+            ExpenseOption::Due(None) => write!(f, "due none"),
+            ExpenseOption::Share(Some(share)) => write!(f, "share {share}"),
+            ExpenseOption::Share(None) => write!(f, "share none"),
+            ExpenseOption::Warn(Some(percent)) => write!(f, "warn {percent}"),
+            ExpenseOption::Warn(None) => write!(f, "warn none"),
+            ExpenseOption::Limit(Some(percent)) => write!(f, "limit {percent}"),
+            ExpenseOption::Limit(None) => write!(f, "limit none"),
+        }
+    }
+}
+
+//Threshold: which of an expense's thresholds a payment went past
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Threshold {
+    Warning,
+    Limit,
+}
+
+//Alert: a payment that took an expense past one of its thresholds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub expense: String,
+    pub threshold: Threshold,
+    pub percent: Percent,
+    //after the payment
+    pub paid: Money,
+    pub expected: Money,
+}
+impl std::fmt::Display for InNotation<'_, Alert> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let InNotation(alert, c) = self;
+        let name = to_title_case(alert.expense.clone());
+        match alert.threshold {
+            Threshold::Warning => write!(f, "{name} reached {} of its budget", alert.percent)?,
+            Threshold::Limit => write!(f, "{name} went past its {} limit", alert.percent)?,
+        }
+        write!(
+            f,
+            ", {} of {} paid",
+            c.format(alert.paid),
+            c.format(alert.expected)
+        )
+    }
+}
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Notation::default().show(self).fmt(f)
+    }
+}
+
+//Position: where to move an expense in the budget's order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    Up(usize),
+    Down(usize),
+    //1 is the top
+    At(usize),
+    Top,
+    Bottom,
+}
+impl Position {
+    //from_args(): "up" or "down" with an optional number of steps, "top", "bottom" or a position counted from 1
+    pub fn from_args(values: &[&str]) -> Result<Position> {
+        let invalid = || Error::InvalidPosition(values.join(" "));
+        let steps = match values.get(1) {
+            Some(n) => n.parse().map_err(|_| invalid())?,
+            None => 1,
+        };
+        let position = match values.first().map(|v| v.to_ascii_lowercase()).as_deref() {
+            Some("up") => Position::Up(steps),
+            Some("down") => Position::Down(steps),
+            Some("top") | Some("first") => Position::Top,
+            Some("bottom") | Some("last") => Position::Bottom,
+            Some(n) => match n.parse() {
+                Ok(0) | Err(_) => return Err(invalid()),
+                Ok(n) => Position::At(n),
+            },
+            None => return Err(invalid()),
+        };
+        match (position, values.len()) {
+            (Position::Up(_) | Position::Down(_), ..=2) | (_, ..=1) => Ok(position),
+            _ => Err(invalid()),
+        }
+    }
+
+    //index(): the 0-based index an expense at `from` moves to, in a list of `len` expenses
+    pub fn index(self, from: usize, len: usize) -> usize {
+        let last = len.saturating_sub(1);
+        match self {
+            Position::Up(n) => from.saturating_sub(n),
+            Position::Down(n) => from.saturating_add(n).min(last),
+            Position::At(n) => n.saturating_sub(1).min(last),
+            Position::Top => 0,
+            Position::Bottom => last,
+        }
+    }
+}
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Position::Up(n) => write!(f, "up {n}"),
+            Position::Down(n) => write!(f, "down {n}"),
+            Position::At(n) => write!(f, "to {n}"),
+            Position::Top => write!(f, "to the top"),
+            Position::Bottom => write!(f, "to the bottom"),
+        }
+    }
+}
+
+//ExpenseSort: the order expenses are shown in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpenseSort {
+    //the budget's own order, in which expenses were added unless moved
+    #[default]
+    Position,
+    Name,
+    //expected amount, largest first
+    Amount,
+    //left to pay, largest first
+    Remaining,
+    //percent of the expected amount paid, highest first
+    PercentUsed,
+}
+impl std::str::FromStr for ExpenseSort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace(['-', '_'], " ").as_str() {
+            "position" | "order" | "none" => Ok(ExpenseSort::Position),
+            "name" => Ok(ExpenseSort::Name),
+            "amount" | "expected" => Ok(ExpenseSort::Amount),
+            "remaining" | "left" => Ok(ExpenseSort::Remaining),
+            "percent" | "percent used" | "used" => Ok(ExpenseSort::PercentUsed),
+            _ => Err(Error::InvalidExpenseSort(s.into())),
+        }
+    }
+}
+impl std::fmt::Display for ExpenseSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpenseSort::Position => write!(f, "position"),
+            ExpenseSort::Name => write!(f, "name"),
+            ExpenseSort::Amount => write!(f, "amount"),
+            ExpenseSort::Remaining => write!(f, "remaining"),
+            ExpenseSort::PercentUsed => write!(f, "percent used"),
+        }
+    }
+}
+
+//parse_toggle(): on/off style values, with a missing value meaning on
+pub fn parse_toggle(value: Option<&str>) -> Result<bool> {
+    match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+        None | Some("on") | Some("yes") | Some("true") => Ok(true),
+        Some("off") | Some("no") | Some("false") => Ok(false),
+        Some(other) => Err(Error::InvalidExpenseOption(other.into())),
+    }
+}
+
+pub fn toggle_str(enabled: bool) -> &'static str {
+    match enabled {
+        true => "on",
+        false => "off",
+    }
+}
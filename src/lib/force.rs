@@ -0,0 +1,48 @@
+//Force: what to do when a payment is more than the remaining balance, from how many times -f was given
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Force {
+    //fail with CannotAfford, frontends ask the user first
+    Confirm,
+    //fail with CannotAfford, without asking
+    NoConfirm,
+    //pay only what is left of the balance
+    UseRemaining,
+    //pay in full, overdrafting the balance
+    Overdraft,
+}
+impl From<u8> for Force {
+    fn from(count: u8) -> Self {
+        match count {
+            0 => Force::Confirm,
+            1 => Force::NoConfirm,
+            2 => Force::UseRemaining,
+            _ => Force::Overdraft,
+        }
+    }
+}
+impl From<Force> for u8 {
+    fn from(force: Force) -> Self {
+        force as u8
+    }
+}
+
+//Forces: a force level for the balance and a separate one for expense limits,
+//the same unless a frontend raises one of them for a single command, as when answering a prompt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Forces {
+    pub balance: Force,
+    pub limit: Force,
+}
+impl From<Force> for Forces {
+    fn from(force: Force) -> Self {
+        Forces {
+            balance: force,
+            limit: force,
+        }
+    }
+}
+impl From<u8> for Forces {
+    fn from(count: u8) -> Self {
+        Force::from(count).into()
+    }
+}
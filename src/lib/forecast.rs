@@ -0,0 +1,49 @@
+use crate::locale::Notation;
+use crate::money::Money;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+//how many days ahead a forecast goes at most, about ten years, as it is simulated one day at a time
+pub const MAX_FORECAST_DAYS: i64 = 3660;
+
+//ForecastEvent: an expected paycheck (positive) or bill (negative)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ForecastEvent {
+    pub name: String,
+    pub amount: Money,
+}
+
+//ForecastDay: everything expected on one day, and the balance at the end of it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ForecastDay {
+    #[serde(with = "date_format")]
+    pub date: Date,
+    pub events: Vec<ForecastEvent>,
+    pub balance: Money,
+}
+
+//Forecast: the balance simulated forward day by day, from expected income and expenses
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Forecast {
+    pub notation: Notation,
+    pub starting_balance: Money,
+    //every day in the forecast, in order
+    pub days: Vec<ForecastDay>,
+}
+impl Forecast {
+    //first_negative(): the first day the balance is expected to go below zero, if it does
+    pub fn first_negative(&self) -> Option<&ForecastDay> {
+        self.days.iter().find(|d| d.balance.is_negative())
+    }
+
+    pub fn lowest(&self) -> Option<&ForecastDay> {
+        self.days.iter().min_by_key(|d| d.balance)
+    }
+
+    //event_days(): only the days something is expected to happen
+    pub fn event_days(&self) -> impl Iterator<Item = &ForecastDay> {
+        self.days.iter().filter(|d| !d.events.is_empty())
+    }
+}
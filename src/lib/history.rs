@@ -0,0 +1,62 @@
+use crate::budget::Budget;
+use crate::ledger::LedgerTail;
+
+use serde::{Deserialize, Serialize};
+
+//how many steps are kept for undo, older steps are forgotten
+pub const HISTORY_LIMIT: usize = 32;
+
+//History: undo and redo stacks of applied BudgetCommands, saved along with the account
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+}
+impl History {
+    //push(): adds a newly applied step, which invalidates anything that could be redone
+    pub(crate) fn push(&mut self, step: Step) {
+        self.undo.push(step);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub(crate) fn pop_undo(&mut self) -> Option<Step> {
+        self.undo.pop()
+    }
+
+    pub(crate) fn pop_redo(&mut self) -> Option<Step> {
+        self.redo.pop()
+    }
+
+    pub(crate) fn push_undo(&mut self, step: Step) {
+        self.undo.push(step);
+    }
+
+    pub(crate) fn push_redo(&mut self, step: Step) {
+        self.redo.push(step);
+    }
+
+    //undo_steps(): descriptions of undoable commands, most recent first
+    pub fn undo_steps(&self) -> impl Iterator<Item = &str> {
+        self.undo.iter().rev().map(|s| s.command.as_str())
+    }
+
+    //redo_steps(): descriptions of redoable commands, next redo first
+    pub fn redo_steps(&self) -> impl Iterator<Item = &str> {
+        self.redo.iter().rev().map(|s| s.command.as_str())
+    }
+}
+
+//Step: a command, along with the Budget state needed to invert it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Step {
+    pub command: String,
+    //the state on the other side of this step, without its own history or ledger
+    pub(crate) state: Box<Budget>,
+    //the ledger on the other side of this step, as the tail that differs from the current one
+    //None = the state carries its whole ledger, as steps saved by older versions do
+    #[serde(default)]
+    pub(crate) ledger: Option<LedgerTail>,
+}
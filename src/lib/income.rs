@@ -0,0 +1,182 @@
+use crate::error::{Error, Result};
+use crate::locale::{InNotation, Notation};
+use crate::money::Money;
+use crate::schedule::{day_in_month, Recurrence};
+use crate::util::add_days;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+//IncomeSource: a named, recurring income
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IncomeSource {
+    pub amount: Money,
+    pub frequency: Frequency,
+}
+impl IncomeSource {
+    //monthly(): the amount normalized to an average month
+    pub fn monthly(&self) -> Money {
+        self.frequency.monthly(self.amount)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Frequency {
+    Weekly,
+    Biweekly,
+    SemiMonthly,
+    Monthly,
+}
+impl Frequency {
+    pub fn per_year(&self) -> i32 {
+        match self {
+            Frequency::Weekly => 52,
+            Frequency::Biweekly => 26,
+            Frequency::SemiMonthly => 24,
+            Frequency::Monthly => 12,
+        }
+    }
+
+    //monthly(): an amount paid at this frequency, normalized to an average month
+    pub fn monthly(&self, cents: Money) -> Money {
+        cents.scaled(self.per_year() as i64, 12)
+    }
+
+    //paydays(): every payday in [from, to], counting on from the past paydays, oldest first, if there were any,
+    //otherwise from a full pay period after `from`; semi-monthly pay falls on the 1st and 15th
+    pub fn paydays(&self, paid: &[Date], from: Date, to: Date) -> Vec<Date> {
+        let last_paid = paid.last().copied();
+        let day_after = |date: Date| date.next_day().unwrap_or(date);
+        let start = match last_paid {
+            Some(date) => day_after(date).max(from),
+            None => day_after(from),
+        };
+        let weekly = |interval: u8| Recurrence::Weekly {
+            anchor: add_days(last_paid.unwrap_or(from), 7 * interval as i64),
+            interval,
+        };
+        let mut dates = match self {
+            Frequency::Weekly => weekly(1).due_between(start, to),
+            Frequency::Biweekly => weekly(2).due_between(start, to),
+            Frequency::SemiMonthly => [1, 15]
+                .into_iter()
+                .flat_map(|day| Recurrence::Monthly { day }.due_between(start, to))
+                .collect(),
+            Frequency::Monthly => Recurrence::Monthly {
+                day: monthly_day(paid).unwrap_or(from.day()),
+            }
+            .due_between(start, to),
+        };
+        dates.sort();
+        dates
+    }
+}
+
+//monthly_day(): the day of the month monthly pay falls on, that of the last payday,
+//unless a short month cut it short, as February does the 31st, then that of the payday before,
+//no two months in a row being short
+fn monthly_day(paid: &[Date]) -> Option<u8> {
+    let last = *paid.last()?;
+    let before = paid.len().checked_sub(2).map(|i| paid[i].day());
+    Some(
+        before
+            .filter(|&day| {
+                day > last.day() && day_in_month(last.year(), last.month(), day) == Some(last)
+            })
+            .unwrap_or(last.day()),
+    )
+}
+impl std::str::FromStr for Frequency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "weekly" => Ok(Frequency::Weekly),
+            "biweekly" | "bi-weekly" => Ok(Frequency::Biweekly),
+            "semimonthly" | "semi-monthly" => Ok(Frequency::SemiMonthly),
+            "monthly" => Ok(Frequency::Monthly),
+            _ => Err(Error::InvalidFrequency(s.into())),
+        }
+    }
+}
+impl std::fmt::Display for Frequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Frequency::Weekly => write!(f, "weekly"),
+            Frequency::Biweekly => write!(f, "biweekly"),
+            Frequency::SemiMonthly => write!(f, "semi-monthly"),
+            Frequency::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+impl std::fmt::Display for InNotation<'_, IncomeSource> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let InNotation(source, notation) = self;
+        write!(f, "{} {}", notation.format(source.amount), source.frequency)
+    }
+}
+impl std::fmt::Display for IncomeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Notation::default().show(self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::parse_date;
+
+    fn dates<const N: usize>(dates: [&str; N]) -> Vec<Date> {
+        dates.map(|d| parse_date(d).unwrap()).to_vec()
+    }
+
+    #[test]
+    fn monthly_paydays_keep_their_day_after_a_short_month() {
+        let paid = dates(["2025-01-31", "2025-02-28"]);
+        let [from, to] = [paid[1], parse_date("2025-06-30").unwrap()];
+        assert_eq!(
+            Frequency::Monthly.paydays(&paid, from, to),
+            dates(["2025-03-31", "2025-04-30", "2025-05-31", "2025-06-30"])
+        );
+        //without an earlier payday there is no telling the 28th from a shortened 31st
+        assert_eq!(
+            Frequency::Monthly.paydays(&paid[1..], from, to)[0],
+            parse_date("2025-03-28").unwrap()
+        );
+        let [from, to] = dates(["2024-01-31", "2024-04-30"]).try_into().unwrap();
+        assert_eq!(
+            Frequency::Monthly.paydays(&[], from, to),
+            dates(["2024-02-29", "2024-03-31", "2024-04-30"])
+        );
+    }
+
+    #[test]
+    fn weekly_paydays_count_from_the_last_payday() {
+        let paid = dates(["2025-01-03"]);
+        let [from, to] = dates(["2025-01-10", "2025-02-14"]).try_into().unwrap();
+        assert_eq!(
+            Frequency::Biweekly.paydays(&paid, from, to),
+            dates(["2025-01-17", "2025-01-31", "2025-02-14"])
+        );
+        assert_eq!(Frequency::Weekly.paydays(&paid, from, to).len(), 6);
+        let [from, to] = dates(["2025-01-01", "2025-02-01"]).try_into().unwrap();
+        assert_eq!(
+            Frequency::SemiMonthly.paydays(&[], from, to),
+            dates(["2025-01-15", "2025-02-01"])
+        );
+    }
+
+    #[test]
+    fn monthly_is_an_average_month() {
+        let monthly = |frequency: &str, cents: i64| {
+            let frequency: Frequency = frequency.parse().unwrap();
+            frequency.monthly(Money::from_cents(cents)).cents()
+        };
+        assert_eq!(monthly("weekly", 10_000), 43_333);
+        assert_eq!(monthly("bi-weekly", 100_000), 216_666);
+        assert_eq!(monthly("semimonthly", 100_000), 200_000);
+        assert_eq!(monthly("Monthly", 100_000), 100_000);
+        assert!("daily".parse::<Frequency>().is_err());
+    }
+}
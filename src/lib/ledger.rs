@@ -0,0 +1,497 @@
+use crate::currency::Currency;
+use crate::debt::Debt;
+use crate::income::IncomeSource;
+use crate::locale::{InNotation, Notation};
+use crate::money::Money;
+use crate::savings::SavingsGoal;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+//Ledger: every change made to a Budget, in the order it happened
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Ledger {
+    next_id: u64,
+    entries: Vec<Transaction>,
+}
+impl Ledger {
+    //record(): appends a new entry stamped with the current time, returning its id
+    pub fn record(&mut self, kind: TransactionKind, memo: Option<String>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(Transaction {
+            id,
+            timestamp: now(),
+            kind,
+            memo,
+        });
+        id
+    }
+
+    pub fn entries(&self) -> &[Transaction] {
+        &self.entries
+    }
+
+    //next_id(): the id the next recorded entry will get
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    //since(): entries recorded from the given id onwards
+    pub fn since(&self, id: u64) -> impl Iterator<Item = &Transaction> {
+        self.entries.iter().filter(move |t| t.id >= id)
+    }
+
+    //income(): total income received in the given entries
+    pub fn income<'a>(entries: impl Iterator<Item = &'a Transaction>) -> Money {
+        entries
+            .filter_map(|t| match t.kind {
+                TransactionKind::Income { amount, .. } => Some(amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Transaction> {
+        self.entries.iter().find(|t| t.id == id)
+    }
+
+    //spent(): total paid out to an expense by the given entries, less refunds
+    pub fn spent<'a>(entries: impl Iterator<Item = &'a Transaction>) -> Money {
+        entries
+            .filter_map(|t| match t.kind {
+                TransactionKind::Payment { amount, .. } => Some(amount),
+                TransactionKind::Refund { amount, .. } => Some(-amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    //reversal_of(): the entry that reversed the given payment, if any
+    pub fn reversal_of(&self, id: u64) -> Option<&Transaction> {
+        self.entries.iter().find(
+            |t| matches!(t.kind, TransactionKind::Refund { reverses: Some(r), .. } if r == id),
+        )
+    }
+
+    //tag_totals(): totals for every tag used in the given entries, sorted by tag
+    pub fn tag_totals<'a>(entries: impl Iterator<Item = &'a Transaction>) -> Vec<TagTotal> {
+        let mut totals: Vec<TagTotal> = Vec::new();
+        for t in entries {
+            for tag in t.tags() {
+                let index = match totals.iter().position(|total| total.tag == tag) {
+                    Some(i) => i,
+                    None => {
+                        totals.push(TagTotal {
+                            tag,
+                            spent: Money::ZERO,
+                            received: Money::ZERO,
+                            entries: 0,
+                        });
+                        totals.len() - 1
+                    }
+                };
+                let total = &mut totals[index];
+                total.entries += 1;
+                match t.kind {
+                    TransactionKind::Income { amount, .. } => {
+                        total.received = total.received.saturating_add(amount)
+                    }
+                    TransactionKind::Payment { amount, .. }
+                    | TransactionKind::DebtPayment { amount, .. } => {
+                        total.spent = total.spent.saturating_add(amount)
+                    }
+                    TransactionKind::Refund { amount, .. } => {
+                        total.spent = total.spent.saturating_sub(amount)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        totals.sort_by(|a, b| a.tag.cmp(&b.tag));
+        totals
+    }
+
+    //balance_change(): net effect of every entry on current_balance
+    pub fn balance_change(&self) -> Money {
+        self.entries.iter().map(|t| t.kind.balance_delta()).sum()
+    }
+
+    //tail(): this ledger from the given id onwards, which is all that differs from any earlier state of it,
+    //as entries are only ever appended
+    pub(crate) fn tail(&self, from: u64) -> LedgerTail {
+        LedgerTail {
+            from,
+            next_id: self.next_id,
+            entries: self.since(from).cloned().collect(),
+        }
+    }
+
+    //replace_tail(): swaps the entries from the tail's first id onwards for the tail's own,
+    //returning the replaced ones as a tail that swaps them back
+    pub(crate) fn replace_tail(&mut self, tail: LedgerTail) -> LedgerTail {
+        let at = self.entries.partition_point(|t| t.id < tail.from);
+        let replaced = LedgerTail {
+            from: tail.from,
+            next_id: self.next_id,
+            entries: self.entries.split_off(at),
+        };
+        self.entries.extend(tail.entries);
+        self.next_id = tail.next_id;
+        replaced
+    }
+}
+
+//LedgerTail: the end of a Ledger from one id onwards, enough to move it to another state
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct LedgerTail {
+    from: u64,
+    next_id: u64,
+    entries: Vec<Transaction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Transaction {
+    pub id: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub kind: TransactionKind,
+    pub memo: Option<String>,
+}
+impl Transaction {
+    //tags(): every #tag written in the memo, lowercased and without the #
+    pub fn tags(&self) -> Vec<String> {
+        self.memo.as_deref().map(parse_tags).unwrap_or_default()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        self.tags().iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+//parse_tags(): words like #work or #reimbursable, which must start with a letter so #4 stays a ledger id
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = text
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| {
+            tag.chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|tag| tag.starts_with(char::is_alphabetic))
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+//TagTotal: money moved by the entries carrying one tag
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TagTotal {
+    pub tag: String,
+    //payments less refunds
+    pub spent: Money,
+    //income, such as reimbursements
+    pub received: Money,
+    pub entries: usize,
+}
+impl TagTotal {
+    //outstanding(): spending not yet received back, like reimbursable expenses still to be paid out
+    pub fn outstanding(&self) -> Money {
+        self.spent.saturating_sub(self.received)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransactionKind {
+    Income {
+        amount: Money,
+        #[serde(default)]
+        source: Option<String>,
+    },
+    Payment {
+        expense: String,
+        amount: Money,
+    },
+    Refund {
+        expense: String,
+        amount: Money,
+        //Some = reverses the payment with this id
+        #[serde(default)]
+        reverses: Option<u64>,
+    },
+    Savings {
+        amount: Money,
+        //None = general savings
+        #[serde(default)]
+        goal: Option<String>,
+    },
+    Withdrawal {
+        amount: Money,
+        //None = general savings
+        goal: Option<String>,
+    },
+    Transfer {
+        amount: Money,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    Clear {
+        expense: String,
+        //amount that had been paid before clearing
+        amount: Money,
+    },
+    Edit {
+        expense: String,
+        change: ExpenseChange,
+    },
+    Paycheck {
+        from: Money,
+        to: Money,
+    },
+    Period {
+        closed: String,
+        opened: String,
+    },
+    Currency {
+        from: Currency,
+        to: Currency,
+    },
+    IncomeSource {
+        name: String,
+        //None = removed
+        source: Option<IncomeSource>,
+    },
+    Goal {
+        name: String,
+        //None = removed
+        goal: Option<SavingsGoal>,
+    },
+    Debt {
+        name: String,
+        //None = removed
+        debt: Option<Debt>,
+    },
+    Interest {
+        debt: String,
+        amount: Money,
+    },
+    DebtPayment {
+        debt: String,
+        amount: Money,
+    },
+    //Adjustment: a change to current_balance no other entry explains, recorded by Budget::repair()
+    Adjustment {
+        amount: Money,
+    },
+    //OpeningBalance: the balance of a budget saved before it had a ledger, recorded by Budget::open_ledger()
+    OpeningBalance {
+        amount: Money,
+    },
+}
+impl TransactionKind {
+    //balance_delta(): signed change this entry made to current_balance
+    pub fn balance_delta(&self) -> Money {
+        match self {
+            TransactionKind::Income { amount, .. } => *amount,
+            TransactionKind::Payment { amount, .. } => -*amount,
+            TransactionKind::Refund { amount, .. } => *amount,
+            TransactionKind::Savings { amount, .. } => -*amount,
+            TransactionKind::Withdrawal { amount, .. } => *amount,
+            TransactionKind::DebtPayment { amount, .. } => -*amount,
+            TransactionKind::Adjustment { amount } => *amount,
+            TransactionKind::OpeningBalance { amount } => *amount,
+            _ => Money::ZERO,
+        }
+    }
+
+    pub fn expense(&self) -> Option<&str> {
+        match self {
+            TransactionKind::Payment { expense, .. }
+            | TransactionKind::Refund { expense, .. }
+            | TransactionKind::Clear { expense, .. }
+            | TransactionKind::Edit { expense, .. } => Some(expense),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ExpenseChange {
+    Created { amount: Money },
+    Amount { from: Money, to: Money },
+    Renamed { from: String },
+    Setting { setting: String },
+    //1-based positions in the budget's order
+    Moved { from: usize, to: usize },
+}
+
+impl std::fmt::Display for InNotation<'_, Transaction> {
+    //fmt(): one line per entry, "#id date time  kind  detail  memo"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let InNotation(t, c) = self;
+        let stamp = t
+            .timestamp
+            .format(time::macros::format_description!(
+                "[year]-[month]-[day] [hour]:[minute]"
+            ))
+            .map_err(|_| std::fmt::Error)?;
+        write!(f, "#{} {stamp}  ", t.id)?;
+
+        use TransactionKind as TK;
+        match &t.kind {
+            TK::Income { amount, source } => match source {
+                Some(name) => write!(
+                    f,
+                    "Income  {}  {}",
+                    to_title_case(name.clone()),
+                    c.format_change(*amount)
+                )?,
+                None => write!(f, "Income  {}", c.format_change(*amount))?,
+            },
+            TK::Payment { expense, amount } => write!(
+                f,
+                "Payment  {}  {}",
+                to_title_case(expense.clone()),
+                c.format_change(-*amount)
+            )?,
+            TK::Refund {
+                expense,
+                amount,
+                reverses,
+            } => {
+                let kind = match reverses {
+                    Some(_) => "Reversal",
+                    None => "Refund",
+                };
+                write!(
+                    f,
+                    "{kind}  {}  {}",
+                    to_title_case(expense.clone()),
+                    c.format_change(*amount)
+                )?;
+                if let Some(id) = reverses {
+                    write!(f, "  (of #{id})")?;
+                }
+            }
+            TK::Savings { amount, goal } => write!(
+                f,
+                "Savings  {}  {}",
+                bucket_name(goal),
+                c.format_change(-*amount)
+            )?,
+            TK::Withdrawal { amount, goal } => write!(
+                f,
+                "Withdrawal  {}  {}",
+                bucket_name(goal),
+                c.format_change(*amount)
+            )?,
+            TK::Transfer { amount, from, to } => write!(
+                f,
+                "Transfer  {} -> {}  {}",
+                bucket_name(from),
+                bucket_name(to),
+                c.format(*amount)
+            )?,
+            TK::Clear { expense, amount } => write!(
+                f,
+                "Clear  {}  ({} paid)",
+                to_title_case(expense.clone()),
+                c.format(*amount)
+            )?,
+            TK::Edit { expense, change } => {
+                let name = to_title_case(expense.clone());
+                match change {
+                    ExpenseChange::Created { amount } => {
+                        write!(f, "New  {name}  {}", c.format(*amount))?
+                    }
+                    ExpenseChange::Amount { from, to } => {
+                        write!(f, "Edit  {name}  {} -> {}", c.format(*from), c.format(*to))?
+                    }
+                    ExpenseChange::Renamed { from } => {
+                        write!(f, "Rename  {} -> {name}", to_title_case(from.clone()))?
+                    }
+                    ExpenseChange::Setting { setting } => write!(f, "Set  {name}  {setting}")?,
+                    ExpenseChange::Moved { from, to } => write!(f, "Move  {name}  {from} -> {to}")?,
+                }
+            }
+            TK::Paycheck { from, to } => {
+                write!(f, "Paycheck  {} -> {}", c.format(*from), c.format(*to))?
+            }
+            TK::Currency { from, to } => write!(f, "Currency  {from} -> {to}")?,
+            TK::Period { closed, opened } => write!(f, "Period  {closed} -> {opened}")?,
+            TK::IncomeSource { name, source } => match source {
+                Some(source) => write!(
+                    f,
+                    "Source  {}  {}",
+                    to_title_case(name.clone()),
+                    c.show(source)
+                )?,
+                None => write!(f, "Source  {}  removed", to_title_case(name.clone()))?,
+            },
+            TK::Goal { name, goal } => {
+                let name = to_title_case(name.clone());
+                match goal {
+                    Some(SavingsGoal {
+                        target,
+                        deadline,
+                        share,
+                        ..
+                    }) => {
+                        write!(f, "Goal  {name}")?;
+                        if let Some(target) = target {
+                            write!(f, "  {}", c.format(*target))?;
+                        }
+                        if let Some(deadline) = deadline {
+                            write!(f, "  by {deadline}")?;
+                        }
+                        if let Some(share) = share {
+                            write!(f, "  {share} of each paycheck")?;
+                        }
+                    }
+                    None => write!(f, "Goal  {name}  removed")?,
+                }
+            }
+            TK::Debt { name, debt } => {
+                let name = to_title_case(name.clone());
+                match debt {
+                    Some(debt) => write!(f, "Debt  {name}  {}", c.show(debt))?,
+                    None => write!(f, "Debt  {name}  removed")?,
+                }
+            }
+            TK::Interest { debt, amount } => write!(
+                f,
+                "Interest  {}  {}",
+                to_title_case(debt.clone()),
+                c.format(*amount)
+            )?,
+            TK::DebtPayment { debt, amount } => write!(
+                f,
+                "Debt payment  {}  {}",
+                to_title_case(debt.clone()),
+                c.format_change(-*amount)
+            )?,
+            TK::Adjustment { amount } => write!(f, "Adjustment  {}", c.format_change(*amount))?,
+            TK::OpeningBalance { amount } => write!(f, "Opening balance  {}", c.format(*amount))?,
+        }
+
+        if let Some(memo) = &t.memo {
+            write!(f, "  \"{memo}\"")?;
+        }
+        Ok(())
+    }
+}
+impl std::fmt::Display for Transaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Notation::default().show(self).fmt(f)
+    }
+}
+
+//bucket_name(): display name of a savings goal, or of general savings
+fn bucket_name(goal: &Option<String>) -> String {
+    to_title_case(goal.clone().unwrap_or("savings".into()))
+}
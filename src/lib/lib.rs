@@ -1,19 +1,62 @@
 pub mod budget;
+pub mod check;
+pub mod currency;
+pub mod debt;
 pub mod error;
+pub mod expense;
+pub mod force;
+pub mod forecast;
+pub mod history;
+pub mod income;
+pub mod ledger;
+pub mod locale;
+pub mod money;
+pub mod period;
+pub mod report;
+pub mod savings;
+pub mod schedule;
+pub mod template;
 pub mod util;
 
+use currency::Currency;
+use debt::{Apr, Compounding};
 use error::{Error, Result};
+use expense::{Alert, ExpenseOption, ExpenseSort, Position};
+use force::{Force, Forces};
+use income::Frequency;
+use ledger::Transaction;
+use locale::{InNotation, Locale, Notation};
+use money::{Money, Percent};
+use report::Report;
+use util::to_title_case;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
 
 pub type Budget = budget::Budget;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum BudgetCommand {
     SetPaycheck {
-        amount: i32,
+        amount: Money,
+    },
+    RaisePaycheck {
+        amount: Money,
     },
     Paid {
-        //None = full paycheck
-        amount: Option<i32>,
+        //None = full paycheck, or the source's usual amount
+        amount: Option<Money>,
+        memo: Option<String>,
+        //None = the paycheck
+        source: Option<String>,
+    },
+    SetIncomeSource {
+        name: String,
+        amount: Money,
+        frequency: Frequency,
+    },
+    RemoveIncomeSource {
+        name: String,
     },
     ClearExpense {
         targets: Vec<String>,
@@ -22,23 +65,274 @@ pub enum BudgetCommand {
     EditExpense {
         target: String,
         new_name: Option<String>,
-        new_amount: Option<i32>,
+        new_amount: Option<Money>,
     },
     NewExpense {
         name: String,
-        amount: i32,
+        amount: Money,
     },
     PayExpense {
         name: String,
         //None = full amount
-        amount: Option<i32>,
+        amount: Option<Money>,
+        memo: Option<String>,
+    },
+    Refund {
+        name: String,
+        amount: Money,
+        memo: Option<String>,
+    },
+    //ReversePayment: undoes the payment recorded in the ledger with this id
+    ReversePayment {
+        id: u64,
+        memo: Option<String>,
+    },
+    //PaySplit: one payment divided between several expenses, all or nothing
+    PaySplit {
+        parts: Vec<(String, Money)>,
+        memo: Option<String>,
     },
     Savings {
         //None = full amount
-        amount: Option<i32>,
+        amount: Option<Money>,
+        memo: Option<String>,
+    },
+    SetExpenseOption {
+        name: String,
+        option: ExpenseOption,
+    },
+    MoveExpense {
+        name: String,
+        position: Position,
+    },
+    SetExpenseSort {
+        sort: ExpenseSort,
+    },
+    SetCurrency {
+        currency: Currency,
+    },
+    SetLocale {
+        locale: Locale,
+    },
+    SetAutomaticPayments {
+        enabled: bool,
+    },
+    ClosePeriod {
+        //None = named after the current month
+        next: Option<String>,
+    },
+    SetSavingsGoal {
+        name: String,
+        target: Option<Money>,
+        deadline: Option<Date>,
+        //set aside this much of every paycheck
+        share: Option<Percent>,
+    },
+    RemoveSavingsGoal {
+        name: String,
+    },
+    Deposit {
+        //None = general savings
+        goal: Option<String>,
+        //None = remaining balance
+        amount: Option<Money>,
+        memo: Option<String>,
+    },
+    Withdraw {
+        //None = general savings
+        goal: Option<String>,
+        amount: Money,
+        memo: Option<String>,
+    },
+    Transfer {
+        //None = general savings
+        from: Option<String>,
+        to: Option<String>,
+        amount: Money,
+    },
+    SetDebt {
+        name: String,
+        balance: Money,
+        apr: Apr,
+        minimum: Money,
+        compounding: Compounding,
+    },
+    RemoveDebt {
+        name: String,
+    },
+    PayDebt {
+        name: String,
+        //None = the minimum payment
+        amount: Option<Money>,
+        memo: Option<String>,
+    },
+    Undo {
+        steps: usize,
     },
+    Redo {
+        steps: usize,
+    },
+    //Repair: fixes everything Budget::check() finds
+    Repair,
     Nothing,
 }
+impl std::fmt::Display for InNotation<'_, BudgetCommand> {
+    //fmt(): short description of the command, as used in the undo history
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use crate::BudgetCommand as BC;
+        let InNotation(cmd, notation) = self;
+
+        match cmd {
+            BC::SetPaycheck { amount } => write!(f, "set paycheck to {}", notation.format(*amount)),
+            BC::RaisePaycheck { amount } => {
+                write!(f, "raise paycheck by {}", notation.format(*amount))
+            }
+            BC::Paid { amount, source, .. } => {
+                write!(f, "get paid")?;
+                if let Some(c) = amount {
+                    write!(f, " {}", notation.format(*c))?;
+                }
+                if let Some(name) = source {
+                    write!(f, " from {name}")?;
+                }
+                Ok(())
+            }
+            BC::SetIncomeSource {
+                name,
+                amount,
+                frequency,
+            } => write!(
+                f,
+                "set income source {name} to {} {frequency}",
+                notation.format(*amount)
+            ),
+            BC::RemoveIncomeSource { name } => write!(f, "remove income source {name}"),
+            BC::ClearExpense {
+                targets,
+                invert_selection,
+            } => match (targets.is_empty(), invert_selection) {
+                (true, _) => write!(f, "clear all"),
+                (false, false) => write!(f, "clear {}", targets.join(", ")),
+                (false, true) => write!(f, "clear all but {}", targets.join(", ")),
+            },
+            BC::EditExpense {
+                target,
+                new_name,
+                new_amount,
+            } => {
+                write!(f, "edit {target}")?;
+                if let Some(c) = new_amount {
+                    write!(f, " to {}", notation.format(*c))?;
+                }
+                if let Some(name) = new_name {
+                    write!(f, " renamed to {name}")?;
+                }
+                Ok(())
+            }
+            BC::NewExpense { name, amount } => {
+                write!(f, "new expense {name} {}", notation.format(*amount))
+            }
+            BC::PayExpense { name, amount, .. } => match amount {
+                Some(c) => write!(f, "pay {name} {}", notation.format(*c)),
+                None => write!(f, "pay {name}"),
+            },
+            BC::Refund { name, amount, .. } => {
+                write!(f, "refund {name} {}", notation.format(*amount))
+            }
+            BC::ReversePayment { id, .. } => write!(f, "reverse payment #{id}"),
+            BC::PaySplit { parts, .. } => {
+                let parts: Vec<String> = parts
+                    .iter()
+                    .map(|(name, c)| format!("{name} {}", notation.format(*c)))
+                    .collect();
+                write!(f, "split payment {}", parts.join(", "))
+            }
+            BC::Savings { amount, .. } => match amount {
+                Some(c) => write!(f, "save {}", notation.format(*c)),
+                None => write!(f, "save all"),
+            },
+            BC::SetExpenseOption { name, option } => write!(f, "set {name} {option}"),
+            BC::MoveExpense { name, position } => write!(f, "move {name} {position}"),
+            BC::SetExpenseSort { sort } => write!(f, "sort expenses by {sort}"),
+            BC::SetCurrency { currency: to } => write!(f, "set currency to {to}"),
+            BC::SetLocale { locale } => write!(f, "set locale to {locale}"),
+            BC::SetAutomaticPayments { enabled } => write!(
+                f,
+                "turn automatic payments {}",
+                expense::toggle_str(*enabled)
+            ),
+            BC::ClosePeriod { next } => match next {
+                Some(name) => write!(f, "close period, start {name}"),
+                None => write!(f, "close period"),
+            },
+            BC::SetSavingsGoal {
+                name,
+                target,
+                deadline,
+                share,
+            } => {
+                write!(f, "set savings goal {name}")?;
+                if let Some(c) = target {
+                    write!(f, " to {}", notation.format(*c))?;
+                }
+                if let Some(date) = deadline {
+                    write!(f, " by {date}")?;
+                }
+                if let Some(share) = share {
+                    write!(f, " saving {share} of each paycheck")?;
+                }
+                Ok(())
+            }
+            BC::RemoveSavingsGoal { name } => write!(f, "remove savings goal {name}"),
+            BC::Deposit { goal, amount, .. } => {
+                let goal = goal.as_deref().unwrap_or("savings");
+                match amount {
+                    Some(c) => write!(f, "deposit {} into {goal}", notation.format(*c)),
+                    None => write!(f, "deposit all into {goal}"),
+                }
+            }
+            BC::Withdraw { goal, amount, .. } => write!(
+                f,
+                "withdraw {} from {}",
+                notation.format(*amount),
+                goal.as_deref().unwrap_or("savings")
+            ),
+            BC::Transfer { from, to, amount } => write!(
+                f,
+                "transfer {} from {} to {}",
+                notation.format(*amount),
+                from.as_deref().unwrap_or("savings"),
+                to.as_deref().unwrap_or("savings")
+            ),
+            BC::SetDebt {
+                name,
+                balance,
+                apr,
+                minimum,
+                ..
+            } => write!(
+                f,
+                "set debt {name} {} at {apr}, {} minimum",
+                notation.format(*balance),
+                notation.format(*minimum)
+            ),
+            BC::RemoveDebt { name } => write!(f, "remove debt {name}"),
+            BC::PayDebt { name, amount, .. } => match amount {
+                Some(c) => write!(f, "pay debt {name} {}", notation.format(*c)),
+                None => write!(f, "pay debt {name} minimum"),
+            },
+            BC::Repair => write!(f, "repair account"),
+            BC::Undo { steps } => write!(f, "undo {steps}"),
+            BC::Redo { steps } => write!(f, "redo {steps}"),
+            BC::Nothing => write!(f, "nothing"),
+        }
+    }
+}
+impl std::fmt::Display for BudgetCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Notation::default().show(self).fmt(f)
+    }
+}
 
 pub type BudgetCommands = Vec<BudgetCommand>;
 impl From<BudgetCommand> for BudgetCommands {
@@ -47,75 +341,537 @@ impl From<BudgetCommand> for BudgetCommands {
     }
 }
 
-pub fn execute_cmd(bud: Budget, cmd: BudgetCommand, force: u8) -> Result<Budget> {
+//Executed: the budget after running commands, with the outcome of each one, in order
+#[derive(Clone, Debug)]
+pub struct Executed {
+    pub budget: Budget,
+    pub outcomes: Vec<Outcome>,
+}
+impl Executed {
+    //warnings(): every alert raised by the commands, in order
+    pub fn warnings(&self) -> impl Iterator<Item = &Alert> {
+        self.outcomes.iter().flat_map(|o| o.warnings.iter())
+    }
+
+    //json(): the budget's report, with the outcomes of the commands under "outcomes"
+    pub fn json(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct Output<'a> {
+            #[serde(flatten)]
+            report: Report,
+            outcomes: &'a [Outcome],
+        }
+        Ok(serde_json::to_string(&Output {
+            report: self.budget.report(),
+            outcomes: &self.outcomes,
+        })?)
+    }
+}
+
+//Outcome: what one command did, for frontends to confirm
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Outcome {
+    //as shown in the undo history
+    pub command: String,
+    pub status: Status,
+    //net change to the balance
+    pub balance_change: Money,
+    //ledger entries the command recorded, with every amount it moved
+    pub entries: Vec<Transaction>,
+    //thresholds its payments went past
+    pub warnings: Vec<Alert>,
+    pub message: String,
+}
+
+//Status: how a command was carried out, a command that fails stops the rest instead
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Applied,
+    Undone,
+    Redone,
+    //nothing to do
+    Skipped,
+}
+
+pub fn execute_cmd(bud: Budget, cmd: BudgetCommand, force: u8) -> Result<Executed> {
     execute_cmds(bud, cmd.into(), force)
 }
 
-pub fn execute_cmds(mut bud: Budget, cmds: BudgetCommands, force: u8) -> Result<Budget> {
-    //TODO: IMPLEMENT CHECKS AND force VALUE
+pub fn execute_cmds(bud: Budget, cmds: BudgetCommands, force: u8) -> Result<Executed> {
+    execute(bud, cmds, force.into())
+}
+
+//execute_cmd_with(): runs one command with separate force levels for the balance and expense limits,
+//for frontends that ask about a payment before raising either for just that command
+pub fn execute_cmd_with(bud: Budget, cmd: BudgetCommand, forces: Forces) -> Result<Executed> {
+    execute(bud, cmd.into(), forces)
+}
+
+fn execute(mut bud: Budget, cmds: BudgetCommands, forces: Forces) -> Result<Executed> {
     use crate::BudgetCommand as BC;
+    let mut outcomes = Vec::new();
 
     for cmd in cmds {
         #[cfg(debug_assertions)]
         println!("[DEV] executing command: {cmd:?}");
 
-        match cmd {
-            BC::SetPaycheck { amount } => {
-                bud.set_income(amount);
-            }
-            BC::Paid { amount } => {
-                match amount {
-                    Some(c) => bud.get_paid_value(c),
-                    None => bud.get_paid(),
-                };
-            }
-            BC::ClearExpense {
-                targets,
-                invert_selection,
-            } => {
-                if targets.is_empty() {
-                    bud.full_refresh();
-                } else {
-                    match invert_selection {
-                        false => {
-                            for exp in targets {
-                                bud.clear_expense(exp.as_str())?;
-                            }
-                        }
-                        true => todo!("clear command selection inversion"),
-                    }
+        let command = bud.notation().show(&cmd).to_string();
+        let (balance, first_entry) = (bud.current_balance(), bud.ledger().next_id());
+        let (status, message, warnings) = match cmd {
+            BC::Undo { steps } => {
+                let mut undone = Vec::new();
+                for _ in 0..steps {
+                    undone.push(bud.undo()?);
                 }
+                (
+                    Status::Undone,
+                    format!("Undid {}", undone.join(", ")),
+                    vec![],
+                )
             }
-            BC::EditExpense {
-                target,
-                new_name,
-                new_amount,
-            } => {
-                if let Some(amount) = new_amount {
-                    bud.edit_expense(&target, amount)?;
+            BC::Redo { steps } => {
+                let mut redone = Vec::new();
+                for _ in 0..steps {
+                    redone.push(bud.redo()?);
                 }
-                if let Some(new_name) = new_name {
-                    bud.rename_expense(&target, &new_name)?;
+                (
+                    Status::Redone,
+                    format!("Redid {}", redone.join(", ")),
+                    vec![],
+                )
+            }
+            BC::Nothing => (Status::Skipped, String::new(), vec![]),
+            cmd => {
+                //every other command is applied as an undoable step
+                let before = bud.snapshot(command.clone());
+                let (message, warnings) = apply_cmd(&mut bud, cmd, forces)?;
+                bud.record_step(before);
+                let message = message.unwrap_or_else(|| to_title_case(command.clone()));
+                (Status::Applied, message, warnings)
+            }
+        };
+
+        outcomes.push(Outcome {
+            command,
+            status,
+            balance_change: bud.current_balance().saturating_sub(balance),
+            //undone and redone commands swap the ledger, so only applied ones have new entries
+            entries: match status {
+                Status::Applied => bud.ledger().since(first_entry).cloned().collect(),
+                _ => Vec::new(),
+            },
+            warnings,
+            message,
+        });
+    }
+
+    Ok(Executed {
+        budget: bud,
+        outcomes,
+    })
+}
+
+//apply_cmd(): runs one command, returning its message, if it has more to say than the command itself,
+//and any alerts its payments raised
+fn apply_cmd(
+    bud: &mut Budget,
+    cmd: BudgetCommand,
+    forces: Forces,
+) -> Result<(Option<String>, Vec<Alert>)> {
+    use crate::BudgetCommand as BC;
+    let (mut message, mut alerts) = (None, Vec::new());
+    let force = forces.balance;
+
+    match cmd {
+        BC::SetPaycheck { amount } => {
+            bud.set_income(amount)?;
+        }
+        BC::RaisePaycheck { amount } => {
+            bud.add_income(amount)?;
+        }
+        BC::Paid {
+            amount,
+            memo,
+            source,
+        } => {
+            let cents = match source {
+                Some(name) => bud.get_paid_from(&name, amount, memo)?,
+                None => {
+                    let cents = amount.unwrap_or(bud.expected_income());
+                    bud.get_paid_value(cents, memo)?;
+                    cents
                 }
+            };
+            let c = bud.notation();
+            let mut received = format!("Received {}", c.format(cents));
+            //savings goals take their share first, automatic payments come out of the rest
+            let mut left = cents;
+            for (goal, saved) in bud.save_shares(cents)? {
+                received = format!(
+                    "{received}. {} set aside for {}",
+                    c.format(saved),
+                    to_title_case(goal)
+                );
+                left = left.saturating_sub(saved);
             }
-            BC::NewExpense { name, amount } => {
-                bud.add_expense(&name, amount);
+            if bud.automatic_payments() {
+                let automatic = bud.make_automatic_payments(left, force)?;
+                received = format!("{received}. {automatic}");
+                alerts = automatic.alerts;
             }
-            BC::PayExpense { name, amount } => {
-                match amount {
-                    Some(c) => bud.make_dynamic_payment(&name, c)?,
-                    None => bud.make_static_payment(&name)?,
-                };
+            message = Some(received);
+        }
+        BC::ClearExpense {
+            targets,
+            invert_selection,
+        } => {
+            if targets.is_empty() {
+                bud.full_refresh();
+            } else {
+                match invert_selection {
+                    false => {
+                        for exp in targets {
+                            bud.clear_expense(exp.as_str())?;
+                        }
+                    }
+                    true => bud.clear_all_except(&targets)?,
+                }
             }
-            BC::Savings { amount } => {
-                match amount {
-                    Some(c) => bud.save(c)?,
-                    None => bud.save_all()?,
-                };
+        }
+        BC::EditExpense {
+            target,
+            new_name,
+            new_amount,
+        } => {
+            if let Some(amount) = new_amount {
+                bud.edit_expense(&target, amount)?;
+            }
+            if let Some(new_name) = new_name {
+                bud.rename_expense(&target, &new_name)?;
             }
-            BC::Nothing => {}
         }
+        BC::NewExpense { name, amount } => {
+            bud.add_expense(&name, amount);
+        }
+        BC::PayExpense { name, amount, memo } => {
+            let payment = match amount {
+                Some(c) => bud.make_dynamic_payment(&name, c, memo, forces)?,
+                None => bud.make_static_payment(&name, memo, forces)?,
+            };
+            message = Some(payment.to_string());
+            alerts = payment.alerts;
+        }
+        BC::Refund { name, amount, memo } => {
+            message = Some(bud.refund(&name, amount, memo, force)?);
+        }
+        BC::ReversePayment { id, memo } => {
+            message = Some(bud.reverse_payment(id, memo, force)?);
+        }
+        BC::PaySplit { parts, memo } => {
+            let payment = bud.make_split_payment(&parts, memo, forces)?;
+            message = Some(payment.to_string());
+            alerts = payment.alerts;
+        }
+        BC::Savings { amount, memo } => {
+            //savings never overdraft, at most they take what is left
+            message = Some(match amount {
+                Some(c) => bud.save(
+                    bud.afford("savings", c, force.min(Force::UseRemaining))?,
+                    memo,
+                )?,
+                None => bud.save_all(memo)?,
+            });
+        }
+        BC::SetExpenseOption { name, option } => {
+            bud.set_expense_option(&name, option)?;
+        }
+        BC::SetIncomeSource {
+            name,
+            amount,
+            frequency,
+        } => {
+            bud.set_income_source(&name, amount, frequency);
+        }
+        BC::RemoveIncomeSource { name } => {
+            bud.remove_income_source(&name)?;
+        }
+        BC::SetCurrency { currency } => {
+            bud.set_currency(currency)?;
+        }
+        BC::MoveExpense { name, position } => {
+            bud.move_expense(&name, position)?;
+        }
+        BC::SetExpenseSort { sort } => {
+            bud.set_expense_sort(sort);
+        }
+        BC::SetLocale { locale } => {
+            bud.set_locale(locale);
+        }
+        BC::SetAutomaticPayments { enabled } => {
+            bud.set_automatic_payments(enabled);
+        }
+        BC::ClosePeriod { next } => {
+            message = Some(bud.close_period(next)?);
+        }
+        BC::SetSavingsGoal {
+            name,
+            target,
+            deadline,
+            share,
+        } => {
+            bud.set_savings_goal(&name, target, deadline, share);
+        }
+        BC::RemoveSavingsGoal { name } => {
+            bud.remove_savings_goal(&name)?;
+        }
+        BC::Deposit { goal, amount, memo } => {
+            let cents = amount.unwrap_or(bud.current_balance());
+            let bucket = goal.as_deref().unwrap_or("savings");
+            let cents = bud.afford(bucket, cents, force.min(Force::UseRemaining))?;
+            message = Some(bud.deposit(goal.as_deref(), cents, memo)?);
+        }
+        BC::Withdraw { goal, amount, memo } => {
+            message = Some(bud.withdraw(goal.as_deref(), amount, memo)?);
+        }
+        BC::Transfer { from, to, amount } => {
+            message = Some(bud.transfer(from.as_deref(), to.as_deref(), amount)?);
+        }
+        BC::SetDebt {
+            name,
+            balance,
+            apr,
+            minimum,
+            compounding,
+        } => {
+            bud.set_debt(&name, balance, apr, minimum, compounding)?;
+        }
+        BC::RemoveDebt { name } => {
+            bud.remove_debt(&name)?;
+        }
+        BC::PayDebt { name, amount, memo } => {
+            message = Some(bud.pay_debt(&name, amount, memo, force)?);
+        }
+        BC::Repair => {
+            let repaired = bud.repair()?;
+            message = Some(format!("Repaired {} problem(s)", repaired.len()));
+        }
+        BC::Undo { .. } | BC::Redo { .. } | BC::Nothing => {}
+    }
+
+    Ok((message, alerts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expense::Threshold;
+    use BudgetCommand as BC;
+
+    fn dollars(n: i64) -> Money {
+        Money::from_cents(n * 100)
+    }
+
+    //budget(): $100 paid in, with $100 food expected
+    fn budget() -> Budget {
+        let cmds = vec![
+            BC::SetPaycheck {
+                amount: dollars(100),
+            },
+            BC::Paid {
+                amount: None,
+                memo: None,
+                source: None,
+            },
+            BC::NewExpense {
+                name: "food".into(),
+                amount: dollars(100),
+            },
+        ];
+        execute_cmds(Budget::new("test"), cmds, 0).unwrap().budget
+    }
+
+    fn pay(amount: i64) -> BudgetCommand {
+        BC::PayExpense {
+            name: "food".into(),
+            amount: Some(dollars(amount)),
+            memo: None,
+        }
+    }
+
+    fn paid(bud: &Budget) -> Money {
+        bud.report().expenses[0].paid
+    }
+
+    //reload(): the budget after saving and loading it again
+    fn reload(bud: &Budget) -> Budget {
+        serde_json::from_str(&serde_json::to_string(bud).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo_across_a_save() {
+        let bud = execute_cmds(budget(), vec![pay(30), pay(20)], 0)
+            .unwrap()
+            .budget;
+        //entries(): each ledger entry's id and amount
+        let entries = |bud: &Budget| -> Vec<(u64, Money)> {
+            bud.ledger()
+                .entries()
+                .iter()
+                .map(|t| (t.id, t.kind.balance_delta()))
+                .collect()
+        };
+        let saved = entries(&bud);
+
+        let bud = execute_cmd(reload(&bud), BC::Undo { steps: 2 }, 0)
+            .unwrap()
+            .budget;
+        assert_eq!(bud.current_balance(), dollars(100));
+        assert_eq!(paid(&bud), Money::ZERO);
+        assert_eq!(entries(&bud), saved[..saved.len() - 2]);
+        assert!(bud.check().is_empty());
+
+        let bud = execute_cmd(reload(&bud), BC::Redo { steps: 2 }, 0)
+            .unwrap()
+            .budget;
+        assert_eq!(bud.current_balance(), dollars(50));
+        assert_eq!(paid(&bud), dollars(50));
+        assert_eq!(entries(&bud), saved);
+        assert_eq!(bud.history().undo_steps().count(), 5);
+        assert!(bud.check().is_empty());
+
+        //a new command after an undo drops what could have been redone
+        let bud = execute_cmds(reload(&bud), vec![BC::Undo { steps: 1 }, pay(5)], 0)
+            .unwrap()
+            .budget;
+        assert_eq!(paid(&bud), dollars(35));
+        assert!(matches!(
+            execute_cmd(reload(&bud), BC::Redo { steps: 1 }, 0),
+            Err(Error::NothingToRedo)
+        ));
+    }
+
+    #[test]
+    fn thresholds_warn_then_block() {
+        let options = vec![
+            BC::SetExpenseOption {
+                name: "food".into(),
+                option: ExpenseOption::Warn(Some("80".parse().unwrap())),
+            },
+            BC::SetExpenseOption {
+                name: "food".into(),
+                option: ExpenseOption::Limit(Some("90".parse().unwrap())),
+            },
+        ];
+        let bud = execute_cmds(budget(), options, 0).unwrap().budget;
+
+        let executed = execute_cmds(bud, vec![pay(70), pay(10)], 0).unwrap();
+        let warnings: Vec<&Alert> = executed.warnings().collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].threshold, Threshold::Warning);
+        assert_eq!(warnings[0].paid, dollars(80));
+        assert!(executed.outcomes[0].warnings.is_empty());
+        let bud = executed.budget;
+
+        for force in [0, 1] {
+            assert!(matches!(
+                execute_cmd(bud.clone(), pay(20), force),
+                Err(Error::OverLimit { allowed, .. }) if allowed == dollars(10)
+            ));
+        }
+        let trimmed = execute_cmd(bud.clone(), pay(20), 2).unwrap();
+        assert_eq!(paid(&trimmed.budget), dollars(90));
+        assert_eq!(trimmed.warnings().count(), 0);
+        let over = execute_cmd(bud, pay(20), 3).unwrap();
+        assert_eq!(paid(&over.budget), dollars(100));
+        assert_eq!(
+            over.warnings().map(|a| a.threshold).collect::<Vec<_>>(),
+            vec![Threshold::Limit]
+        );
+    }
+
+    #[test]
+    fn a_limit_override_still_checks_the_balance() {
+        let limit = BC::SetExpenseOption {
+            name: "food".into(),
+            option: ExpenseOption::Limit(Some("90".parse().unwrap())),
+        };
+        let bud = execute_cmd(budget(), limit, 0).unwrap().budget;
+        let past_limit = Forces {
+            balance: Force::Confirm,
+            limit: Force::Overdraft,
+        };
+
+        let over = execute_cmd_with(bud, pay(95), past_limit).unwrap();
+        assert_eq!(over.budget.current_balance(), dollars(5));
+        assert!(matches!(
+            execute_cmd_with(over.budget.clone(), pay(10), past_limit),
+            Err(Error::CannotAfford { .. })
+        ));
+        let overdraft = Forces {
+            balance: Force::Overdraft,
+            limit: Force::Confirm,
+        };
+        assert!(matches!(
+            execute_cmd_with(over.budget, pay(10), overdraft),
+            Err(Error::OverLimit { .. })
+        ));
     }
 
-    Ok(bud)
+    #[test]
+    fn force_levels() {
+        let bud = execute_cmd(budget(), pay(60), 0).unwrap().budget;
+
+        //0 fails for the frontend to ask, and 1 fails without asking
+        for force in [0, 1] {
+            assert!(matches!(
+                execute_cmd(bud.clone(), pay(50), force),
+                Err(Error::CannotAfford { remaining_balance, .. }) if remaining_balance == dollars(40)
+            ));
+        }
+        //2 pays what is left
+        let trimmed = execute_cmd(bud.clone(), pay(50), 2).unwrap();
+        assert_eq!(trimmed.outcomes[0].balance_change, dollars(-40));
+        assert_eq!(trimmed.budget.current_balance(), Money::ZERO);
+        //3 overdrafts
+        let over = execute_cmd(bud.clone(), pay(50), 3).unwrap();
+        assert_eq!(over.budget.current_balance(), dollars(-10));
+        assert_eq!(paid(&over.budget), dollars(110));
+        //with nothing left, 2 still fails
+        assert!(matches!(
+            execute_cmd(trimmed.budget, pay(1), 2),
+            Err(Error::CannotAfford { .. })
+        ));
+        assert!(over.budget.check().is_empty());
+    }
+
+    #[test]
+    fn clear_all_but_the_named_expenses() {
+        let setup = vec![
+            BC::NewExpense {
+                name: "gas".into(),
+                amount: dollars(50),
+            },
+            pay(30),
+            BC::PayExpense {
+                name: "gas".into(),
+                amount: Some(dollars(20)),
+                memo: None,
+            },
+        ];
+        let bud = execute_cmds(budget(), setup, 0).unwrap().budget;
+        let clear = |targets: &[&str]| BC::ClearExpense {
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            invert_selection: true,
+        };
+        assert!(matches!(
+            execute_cmd(bud.clone(), clear(&["fun"]), 0),
+            Err(Error::ExpenseDoesNotExist(_))
+        ));
+
+        let cleared = execute_cmd(bud, clear(&["Food"]), 0).unwrap().budget;
+        let paid: Vec<Money> = cleared.report().expenses.iter().map(|e| e.paid).collect();
+        assert_eq!(paid, vec![dollars(30), Money::ZERO]);
+        assert!(cleared.check().is_empty());
+    }
 }
@@ -0,0 +1,305 @@
+use crate::currency::Currency;
+use crate::error::{Error, Result};
+use crate::money::Money;
+use crate::util::parse_amount;
+
+use serde::{Deserialize, Serialize};
+
+//SymbolPosition: where the currency symbol goes around the number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolPosition {
+    //$1,234.56, or KD 1,234.567 for symbols made of letters
+    Before,
+    //€ 1.234,56
+    BeforeSpaced,
+    //1.234,56 €
+    After,
+}
+
+//NegativeStyle: how negative amounts are written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NegativeStyle {
+    //-$1,234.56
+    Minus,
+    //($1,234.56)
+    Parentheses,
+}
+
+//LOCALES: name, decimal mark, digit grouping separator, symbol position and negative style of every supported locale
+const LOCALES: &[(&str, char, char, SymbolPosition, NegativeStyle)] = &[
+    (
+        "en-US",
+        '.',
+        ',',
+        SymbolPosition::Before,
+        NegativeStyle::Minus,
+    ),
+    (
+        "en-GB",
+        '.',
+        ',',
+        SymbolPosition::Before,
+        NegativeStyle::Minus,
+    ),
+    (
+        "ja-JP",
+        '.',
+        ',',
+        SymbolPosition::Before,
+        NegativeStyle::Minus,
+    ),
+    (
+        "accounting",
+        '.',
+        ',',
+        SymbolPosition::Before,
+        NegativeStyle::Parentheses,
+    ),
+    (
+        "de-DE",
+        ',',
+        '.',
+        SymbolPosition::After,
+        NegativeStyle::Minus,
+    ),
+    (
+        "es-ES",
+        ',',
+        '.',
+        SymbolPosition::After,
+        NegativeStyle::Minus,
+    ),
+    (
+        "it-IT",
+        ',',
+        '.',
+        SymbolPosition::After,
+        NegativeStyle::Minus,
+    ),
+    (
+        "nl-NL",
+        ',',
+        '.',
+        SymbolPosition::BeforeSpaced,
+        NegativeStyle::Minus,
+    ),
+    (
+        "pt-BR",
+        ',',
+        '.',
+        SymbolPosition::BeforeSpaced,
+        NegativeStyle::Minus,
+    ),
+    (
+        "de-CH",
+        '.',
+        '\'',
+        SymbolPosition::BeforeSpaced,
+        NegativeStyle::Minus,
+    ),
+];
+
+//Locale: how numbers are written, saved as its name
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+    name: &'static str,
+    decimal: char,
+    group: char,
+    symbol: SymbolPosition,
+    negative: NegativeStyle,
+}
+impl Locale {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn decimal(&self) -> char {
+        self.decimal
+    }
+
+    pub fn group(&self) -> char {
+        self.group
+    }
+
+    pub fn symbol_position(&self) -> SymbolPosition {
+        self.symbol
+    }
+
+    pub fn negative_style(&self) -> NegativeStyle {
+        self.negative
+    }
+
+    //names(): every supported locale, for help text
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        LOCALES.iter().map(|(name, ..)| *name)
+    }
+}
+impl Default for Locale {
+    fn default() -> Self {
+        "en-US".parse().expect("en-us-is-supported")
+    }
+}
+impl std::str::FromStr for Locale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        LOCALES
+            .iter()
+            .find(|(name, ..)| {
+                name.eq_ignore_ascii_case(s) || name.replace('-', "_").eq_ignore_ascii_case(s)
+            })
+            .map(|&(name, decimal, group, symbol, negative)| Locale {
+                name,
+                decimal,
+                group,
+                symbol,
+                negative,
+            })
+            .ok_or_else(|| Error::InvalidLocale(s.into()))
+    }
+}
+impl Serialize for Locale {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+impl<'de> Deserialize<'de> for Locale {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.name)
+    }
+}
+
+//Notation: how amounts are written and read, a currency in a locale
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Notation {
+    pub currency: Currency,
+    pub locale: Locale,
+}
+impl Notation {
+    pub fn new(currency: Currency, locale: Locale) -> Notation {
+        Notation { currency, locale }
+    }
+
+    //format(): an amount of minor units, grouped and placed as the locale writes it
+    pub fn format(&self, amount: Money) -> String {
+        let (c, l) = (self.currency, self.locale);
+        let minor = amount.cents().unsigned_abs();
+        let scale = 10u64.pow(c.minor_units() as u32);
+
+        let whole = (minor / scale).to_string();
+        let mut number = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i).is_multiple_of(3) {
+                number.push(l.group);
+            }
+            number.push(digit);
+        }
+        if c.minor_units() > 0 {
+            number.push(l.decimal);
+            number.push_str(&format!(
+                "{:0width$}",
+                minor % scale,
+                width = c.minor_units() as usize
+            ));
+        }
+
+        let body = match l.symbol {
+            SymbolPosition::Before => format!("{}{number}", c.prefix()),
+            SymbolPosition::BeforeSpaced => format!("{} {number}", c.symbol()),
+            SymbolPosition::After => format!("{number} {}", c.symbol()),
+        };
+        match (amount.is_negative(), l.negative) {
+            (false, _) => body,
+            (true, NegativeStyle::Minus) => format!("-{body}"),
+            (true, NegativeStyle::Parentheses) => format!("({body})"),
+        }
+    }
+
+    //format_change(): like format(), with a + on amounts added to a balance
+    pub fn format_change(&self, amount: Money) -> String {
+        match amount.is_negative() {
+            true => self.format(amount),
+            false => format!("+{}", self.format(amount)),
+        }
+    }
+
+    //parse(): an amount written in this notation, with the currency's symbol or code, into minor units
+    //anything format() writes parses back to the same amount
+    pub fn parse(&self, s: &str) -> Result<Money> {
+        let (c, l) = (self.currency, self.locale);
+        parse_amount(
+            s,
+            &[c.symbol(), c.code()],
+            c.minor_units() as u32,
+            l.decimal,
+            l.group,
+        )
+        .map(Money::from_cents)
+    }
+
+    pub fn show<T>(self, value: &T) -> InNotation<'_, T> {
+        InNotation(value, self)
+    }
+}
+impl From<Currency> for Notation {
+    fn from(currency: Currency) -> Self {
+        Notation {
+            currency,
+            locale: Locale::default(),
+        }
+    }
+}
+
+//InNotation: shows the amounts of a value that does not carry its own notation
+pub struct InNotation<'a, T>(pub &'a T, pub Notation);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //one currency of each shape: a symbol, a letter symbol, no minor units and three minor units
+    const CURRENCIES: &[&str] = &["USD", "EUR", "CNY", "CHF", "JPY", "KWD"];
+
+    #[test]
+    fn format_parses_back_in_every_locale() {
+        let amounts = [0, 1, -1, 99, 100_000, -123_456_789, i64::MAX, -i64::MAX];
+        for currency in CURRENCIES.iter().map(|c| c.parse::<Currency>().unwrap()) {
+            for locale in Locale::names().map(|n| n.parse::<Locale>().unwrap()) {
+                let notation = Notation::new(currency, locale);
+                for cents in amounts.map(Money::from_cents) {
+                    let written = notation.format(cents);
+                    assert_eq!(notation.parse(&written).unwrap(), cents, "{written:?}");
+                    let change = notation.format_change(cents);
+                    assert_eq!(notation.parse(&change).unwrap(), cents, "{change:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn format_follows_the_locale() {
+        let euro: Currency = "EUR".parse().unwrap();
+        let cents = Money::from_cents(-123_456);
+        let write = |locale: &str| Notation::new(euro, locale.parse().unwrap()).format(cents);
+        assert_eq!(write("en-US"), "-€1,234.56");
+        assert_eq!(write("accounting"), "(€1,234.56)");
+        assert_eq!(write("de-DE"), "-1.234,56 €");
+        assert_eq!(write("nl_NL"), "-€ 1.234,56");
+        assert_eq!(write("de-CH"), "-€ 1'234.56");
+        let yen = Notation::from("JPY".parse::<Currency>().unwrap());
+        assert_eq!(yen.format(Money::from_cents(1_000)), "¥1,000");
+        assert_eq!(yen.parse("JPY 1,000").unwrap(), Money::from_cents(1_000));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::error::{Error, Result};
+use crate::locale::Notation;
+use crate::util::{parse_amount, parse_dollar_string};
+
+use serde::{Deserialize, Serialize};
+
+//Money: an exact amount of cents
+//serialized as a bare integer, so save files from when amounts were i32 still load
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(transparent)]
+pub struct Money(i64);
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    //checked_add(): the sum, or MoneyOverflow if it is out of range
+    pub fn checked_add(self, other: Money) -> Result<Money> {
+        self.0
+            .checked_add(other.0)
+            .map(Money)
+            .ok_or(Error::MoneyOverflow)
+    }
+
+    //checked_sub(): the difference, or MoneyOverflow if it is out of range
+    pub fn checked_sub(self, other: Money) -> Result<Money> {
+        self.0
+            .checked_sub(other.0)
+            .map(Money)
+            .ok_or(Error::MoneyOverflow)
+    }
+
+    //checked_mul(): the amount times n, or MoneyOverflow if it is out of range
+    pub fn checked_mul(self, n: i64) -> Result<Money> {
+        self.0.checked_mul(n).map(Money).ok_or(Error::MoneyOverflow)
+    }
+
+    //saturating_add(): for totals that are only shown, never stored
+    pub fn saturating_add(self, other: Money) -> Money {
+        Money(self.0.saturating_add(other.0))
+    }
+
+    //saturating_sub(): for differences that are only shown, never stored
+    pub fn saturating_sub(self, other: Money) -> Money {
+        Money(self.0.saturating_sub(other.0))
+    }
+
+    //saturating_mul(): for totals that are only shown, never stored
+    pub fn saturating_mul(self, n: i64) -> Money {
+        Money(self.0.saturating_mul(n))
+    }
+
+    //scaled(): the amount times numerator/denominator, rounded toward zero
+    pub fn scaled(self, numerator: i64, denominator: i64) -> Money {
+        let scaled = self.0 as i128 * numerator as i128 / denominator as i128;
+        Money(scaled.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+}
+
+impl From<i32> for Money {
+    fn from(cents: i32) -> Self {
+        Money(cents as i64)
+    }
+}
+
+impl std::ops::Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.saturating_neg())
+    }
+}
+
+//sums saturate, like saturating_add()
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Money::saturating_add)
+    }
+}
+impl<'a> std::iter::Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+impl std::str::FromStr for Money {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_dollar_string(s).map(Money)
+    }
+}
+
+//formats as $X,XXX.XX or -$X,XXX.XX, respecting width and alignment
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&Notation::default().format(*self))
+    }
+}
+
+//Percent: a share of an amount, exact to a thousandth of a percent
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Percent(u32);
+impl Percent {
+    //from_thousandths(): 12500 is 12.5%
+    pub const fn from_thousandths(thousandths: u32) -> Percent {
+        Percent(thousandths)
+    }
+
+    pub const fn thousandths(self) -> u32 {
+        self.0
+    }
+
+    //of(): this share of an amount, rounded half away from zero, or MoneyOverflow if it is out of range
+    pub fn of(self, amount: Money) -> Result<Money> {
+        i64::try_from(self.share(amount))
+            .map(Money)
+            .map_err(|_| Error::MoneyOverflow)
+    }
+
+    //saturating_of(): like of(), for shares that are only shown or compared, never stored
+    pub fn saturating_of(self, amount: Money) -> Money {
+        let share = self.share(amount);
+        Money(share.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+
+    fn share(self, amount: Money) -> i128 {
+        let numerator = amount.0 as i128 * self.0 as i128;
+        let half = numerator.signum() * 50_000;
+        (numerator + half) / 100_000
+    }
+}
+impl std::str::FromStr for Percent {
+    type Err = Error;
+
+    //from_str(): "10" or "12.5%"
+    fn from_str(s: &str) -> Result<Self> {
+        parse_amount(s, &["%"], 3, '.', ',')
+            .ok()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Percent)
+            .ok_or_else(|| Error::InvalidPercent(s.into()))
+    }
+}
+impl std::fmt::Display for Percent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fraction = format!("{:03}", self.0 % 1000);
+        match fraction.trim_end_matches('0') {
+            "" => write!(f, "{}%", self.0 / 1000),
+            fraction => write!(f, "{}.{fraction}%", self.0 / 1000),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: Money = Money::from_cents(i64::MAX);
+    const MIN: Money = Money::from_cents(i64::MIN);
+
+    #[test]
+    fn checked_arithmetic_overflows() {
+        let one = Money::from_cents(1);
+        assert!(matches!(MAX.checked_add(one), Err(Error::MoneyOverflow)));
+        assert!(matches!(MIN.checked_sub(one), Err(Error::MoneyOverflow)));
+        assert!(matches!(MAX.checked_mul(2), Err(Error::MoneyOverflow)));
+        assert!(matches!(MIN.checked_mul(-1), Err(Error::MoneyOverflow)));
+        assert_eq!(MAX.checked_sub(one).unwrap().cents(), i64::MAX - 1);
+    }
+
+    #[test]
+    fn saturating_arithmetic_stops_at_the_limits() {
+        let one = Money::from_cents(1);
+        assert_eq!(MAX.saturating_add(one), MAX);
+        assert_eq!(MIN.saturating_sub(one), MIN);
+        assert_eq!(MIN.saturating_mul(2), MIN);
+        assert_eq!(-MIN, MAX);
+        assert_eq!(
+            [MAX, MAX, MIN].iter().sum::<Money>(),
+            MIN.saturating_add(MAX)
+        );
+        assert_eq!(MAX.scaled(3, 2), MAX);
+        assert_eq!(Money::from_cents(100).scaled(1, 3).cents(), 33);
+    }
+
+    #[test]
+    fn percent_of_rounds_half_away_from_zero() {
+        let half: Percent = "12.5%".parse().unwrap();
+        assert_eq!(half.of(Money::from_cents(100)).unwrap().cents(), 13);
+        assert_eq!(half.of(Money::from_cents(-100)).unwrap().cents(), -13);
+        let double: Percent = "200".parse().unwrap();
+        assert!(matches!(double.of(MAX), Err(Error::MoneyOverflow)));
+        assert_eq!(double.saturating_of(MAX), MAX);
+        assert_eq!(double.of(MAX.scaled(1, 2)).unwrap().cents(), i64::MAX - 1);
+        assert!("-5%".parse::<Percent>().is_err());
+    }
+}
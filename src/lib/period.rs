@@ -0,0 +1,139 @@
+use crate::currency::Currency;
+use crate::locale::{InNotation, Notation};
+use crate::money::Money;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::Date;
+
+//Period: the budget period currently in progress
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Period {
+    pub name: String,
+    #[serde(with = "date_format")]
+    pub started: Date,
+    //id of the first ledger entry that belongs to this period
+    pub first_entry: u64,
+    //unspent amounts rolled over from the previous period, per expense
+    pub carried: HashMap<String, Money>,
+}
+impl Period {
+    //new(): factory method, returning a fresh Period starting today
+    pub fn new(name: impl Into<String>, first_entry: u64) -> Period {
+        Period {
+            name: name.into(),
+            started: now().date(),
+            first_entry,
+            carried: HashMap::new(),
+        }
+    }
+
+    //carried_for(): amount rolled over into the given expense, if any
+    pub fn carried_for(&self, name: &str) -> Money {
+        self.carried.get(name).copied().unwrap_or_default()
+    }
+}
+impl Default for Period {
+    fn default() -> Self {
+        Period::new(default_period_name(|_| false), 0)
+    }
+}
+
+//default_period_name(): the current month as YYYY-MM, then YYYY-MM-DD, then YYYY-MM-DD-N, whichever is not taken
+pub fn default_period_name(taken: impl Fn(&str) -> bool) -> String {
+    let today = now().date();
+    let month = format!("{:04}-{:02}", today.year(), today.month() as u8);
+    let day = format!("{month}-{:02}", today.day());
+
+    [month, day.clone()]
+        .into_iter()
+        .chain((2..).map(|n| format!("{day}-{n}")))
+        .find(|name| !taken(name))
+        .unwrap_or(day)
+}
+
+//PeriodSnapshot: an archived, closed Period
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PeriodSnapshot {
+    pub name: String,
+    #[serde(with = "date_format")]
+    pub started: Date,
+    #[serde(with = "date_format")]
+    pub closed: Date,
+    pub income: Money,
+    pub balance: Money,
+    pub savings: Money,
+    pub expenses: Vec<ExpenseSnapshot>,
+    #[serde(default)]
+    pub currency: Currency,
+}
+impl PeriodSnapshot {
+    pub fn expense(&self, name: &str) -> Option<&ExpenseSnapshot> {
+        self.expenses.iter().find(|e| e.name == name)
+    }
+
+    pub fn total_expected(&self) -> Money {
+        self.expenses.iter().map(|e| e.expected).sum()
+    }
+
+    pub fn total_actual(&self) -> Money {
+        self.expenses.iter().map(|e| e.actual).sum()
+    }
+}
+
+//ExpenseSnapshot: expected vs. actual for one expense over a closed period
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExpenseSnapshot {
+    pub name: String,
+    //includes anything carried over into the period
+    pub expected: Money,
+    pub actual: Money,
+    //amount rolled over into the next period
+    pub rolled_over: Money,
+}
+
+impl std::fmt::Display for InNotation<'_, PeriodSnapshot> {
+    //fmt(): Display String has a header with the name and dates, followed by totals and then all expenses
+    //amounts are in the currency the period was closed in, written in the given locale
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let InNotation(period, notation) = self;
+        writeln!(
+            f,
+            "==={{ Period {} ({} to {}) }}===",
+            period.name, period.started, period.closed
+        )?;
+        let c = Notation::new(period.currency, notation.locale);
+        writeln!(f, "Income: {}", c.format(period.income))?;
+        writeln!(f, "Balance: {}", c.format(period.balance))?;
+        writeln!(f, "Savings: {}", c.format(period.savings))?;
+        writeln!(
+            f,
+            "Spent: {}/{}",
+            c.format(period.total_actual()),
+            c.format(period.total_expected())
+        )?;
+        writeln!(f, "\nExpenses:")?;
+
+        for exp in period.expenses.iter() {
+            write!(
+                f,
+                "{}: {}/{}",
+                to_title_case(exp.name.clone()),
+                c.format(exp.actual),
+                c.format(exp.expected)
+            )?;
+            if exp.rolled_over != Money::ZERO {
+                write!(f, " ({} rolled over)", c.format(exp.rolled_over))?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+impl std::fmt::Display for PeriodSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Notation::default().show(self).fmt(f)
+    }
+}
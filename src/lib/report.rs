@@ -0,0 +1,200 @@
+use crate::debt::Debt;
+use crate::error::Result;
+use crate::expense::{ExpenseSort, IncomeShare};
+use crate::income::IncomeSource;
+use crate::locale::Notation;
+use crate::money::Money;
+use crate::savings::GoalProgress;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+//BudgetStatus: how what was paid to an expense compares to what was expected
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetStatus {
+    Under,
+    OnBudget,
+    Over,
+}
+impl BudgetStatus {
+    pub fn of(paid: Money, expected: Money) -> BudgetStatus {
+        match paid.cmp(&expected) {
+            std::cmp::Ordering::Less => BudgetStatus::Under,
+            std::cmp::Ordering::Equal => BudgetStatus::OnBudget,
+            std::cmp::Ordering::Greater => BudgetStatus::Over,
+        }
+    }
+}
+
+//ExpenseReport: expected vs. actual for one expense in the current period
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExpenseReport {
+    pub name: String,
+    //includes anything carried over into the period
+    pub expected: Money,
+    pub paid: Money,
+    //negative when over budget
+    pub remaining: Money,
+    //None when nothing is expected
+    pub percent_used: Option<i32>,
+    pub status: BudgetStatus,
+    pub automatic: bool,
+    #[serde(with = "date_format::option")]
+    pub next_due: Option<Date>,
+    //the rule the expected amount follows, None for a fixed amount
+    #[serde(default)]
+    pub share: Option<IncomeShare>,
+}
+
+//Report: everything shown about a budget, computed once for every frontend to render
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Report {
+    pub account: String,
+    pub period: String,
+    pub notation: Notation,
+    pub balance: Money,
+    pub paycheck: Money,
+    pub income_sources: Vec<(String, IncomeSource)>,
+    pub monthly_income: Money,
+    pub savings: Money,
+    pub goals: Vec<GoalProgress>,
+    //with interest owed through today, sorted by name
+    pub debts: Vec<(String, Debt)>,
+    pub automatic_payments: bool,
+    //in the budget's order, or its chosen sort
+    pub expenses: Vec<ExpenseReport>,
+    pub total_expected: Money,
+    pub total_paid: Money,
+    //only what is still left to pay, overspending is not counted against it
+    pub total_remaining: Money,
+    //monthly income not expected to go to any expense or savings goal
+    pub unallocated: Money,
+    //the balance once everything left to pay is paid
+    pub projected_balance: Money,
+}
+impl Report {
+    pub fn json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    //sort_expenses(): a stable sort, so ties keep the budget's order
+    pub fn sort_expenses(expenses: &mut [ExpenseReport], sort: ExpenseSort) {
+        use std::cmp::Reverse;
+        match sort {
+            ExpenseSort::Position => {}
+            ExpenseSort::Name => expenses.sort_by(|a, b| a.name.cmp(&b.name)),
+            ExpenseSort::Amount => expenses.sort_by_key(|e| Reverse(e.expected)),
+            ExpenseSort::Remaining => expenses.sort_by_key(|e| Reverse(e.remaining)),
+            ExpenseSort::PercentUsed => expenses.sort_by_key(|e| Reverse(e.percent_used)),
+        }
+    }
+}
+
+impl Report {
+    //header(): account, balance, income, savings, goals and debts, everything shown above the expenses
+    pub fn header(&self) -> String {
+        let c = self.notation;
+        let mut out = vec![
+            format!("==={{ Welcome, {}! }}===", self.account),
+            format!("Period: {}", self.period),
+            format!("Balance: {}", c.format(self.balance)),
+            format!("Income: {}", c.format(self.paycheck)),
+        ];
+        if !self.income_sources.is_empty() {
+            out.push(format!("Monthly income: {}", c.format(self.monthly_income)));
+            for (name, source) in self.income_sources.iter() {
+                out.push(format!(
+                    "\t{}: {}",
+                    to_title_case(name.clone()),
+                    c.show(source)
+                ));
+            }
+        }
+        out.push(format!("Savings: {}", c.format(self.savings)));
+        for goal in self.goals.iter() {
+            out.push(format!("\t{goal}"));
+        }
+        if !self.debts.is_empty() {
+            let owed: Money = self.debts.iter().map(|(_, d)| d.balance).sum();
+            out.push(format!("Debts: {}", c.format(owed)));
+            for (name, debt) in self.debts.iter() {
+                out.push(format!(
+                    "\t{}: {}",
+                    to_title_case(name.clone()),
+                    c.show(debt)
+                ));
+            }
+        }
+        if self.automatic_payments {
+            out.push(String::from("Automatic payments: on"));
+        }
+        out.join("\n")
+    }
+
+    //totals(): spending totals shown below the expenses, empty without any expenses
+    pub fn totals(&self) -> String {
+        if self.expenses.is_empty() {
+            return String::new();
+        }
+        let c = self.notation;
+        [
+            format!(
+                "Spent: {}/{}, {} left to pay",
+                c.format(self.total_paid),
+                c.format(self.total_expected),
+                c.format(self.total_remaining)
+            ),
+            format!("Unallocated income: {}", c.format(self.unallocated)),
+            format!("Projected balance: {}", c.format(self.projected_balance)),
+        ]
+        .join("\n")
+    }
+}
+
+impl std::fmt::Display for Report {
+    //fmt(): Display String has a header, with account, followed by balance and expected pay, then all expenses and totals
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = self.notation;
+        writeln!(f, "{}", self.header())?;
+        writeln!(f, "\nExpenses:")?;
+
+        for exp in self.expenses.iter() {
+            write!(
+                f,
+                "{}{}: {}/{}",
+                to_title_case(exp.name.clone()),
+                if exp.automatic { " (auto)" } else { "" },
+                c.format(exp.paid),
+                c.format(exp.expected)
+            )?;
+            if exp.status == BudgetStatus::Over {
+                write!(f, " (over by {})", c.format(-exp.remaining))?;
+            }
+            if let Some(share) = exp.share {
+                write!(f, " ({share})")?;
+            }
+            if let Some(date) = exp.next_due {
+                write!(f, " (due {date})")?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.expenses.is_empty() {
+            writeln!(f, "\n{}", self.totals())?;
+        }
+        Ok(())
+    }
+}
+
+//budget_report(): the report of a budget given as a JS object, for web frontends to render
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn budget_report(budget: JsValue) -> std::result::Result<JsValue, JsValue> {
+    let budget: crate::Budget = serde_wasm_bindgen::from_value(budget)?;
+    Ok(serde_wasm_bindgen::to_value(&budget.report())?)
+}
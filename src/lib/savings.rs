@@ -0,0 +1,79 @@
+use crate::locale::Notation;
+use crate::money::{Money, Percent};
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+//SavingsGoal: a named savings bucket, with an optional target amount and date
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SavingsGoal {
+    pub saved: Money,
+    pub target: Option<Money>,
+    #[serde(default, with = "date_format::option")]
+    pub deadline: Option<Date>,
+    //set aside this much of every paycheck received
+    #[serde(default)]
+    pub share: Option<Percent>,
+}
+
+//GoalProgress: how far along a savings goal is, and what it takes to finish on time
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GoalProgress {
+    pub name: String,
+    pub saved: Money,
+    pub target: Option<Money>,
+    #[serde(with = "date_format::option")]
+    pub deadline: Option<Date>,
+    //paychecks expected before the deadline
+    pub paychecks_left: Option<i32>,
+    //amount to set aside from each of those paychecks
+    pub per_paycheck: Option<Money>,
+    pub share: Option<Percent>,
+    pub notation: Notation,
+}
+impl GoalProgress {
+    pub fn remaining(&self) -> Option<Money> {
+        self.target
+            .map(|t| t.saturating_sub(self.saved).max(Money::ZERO))
+    }
+
+    pub fn percent(&self) -> Option<i32> {
+        match self.target {
+            Some(t) if t > Money::ZERO => {
+                Some((self.saved.cents() as i128 * 100 / t.cents() as i128) as i32)
+            }
+            _ => None,
+        }
+    }
+}
+impl std::fmt::Display for GoalProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = self.notation;
+        write!(
+            f,
+            "{}: {}",
+            to_title_case(self.name.clone()),
+            c.format(self.saved)
+        )?;
+        if let (Some(target), Some(percent)) = (self.target, self.percent()) {
+            write!(f, "/{} ({percent}%)", c.format(target))?;
+        }
+        if let Some(deadline) = self.deadline {
+            write!(f, " by {deadline}")?;
+        }
+        if let Some(share) = self.share {
+            write!(f, ", {share} of each paycheck")?;
+        }
+        match (self.remaining(), self.per_paycheck, self.paychecks_left) {
+            (Some(Money::ZERO), _, _) => write!(f, ", reached!"),
+            (Some(_), Some(per), Some(left)) => {
+                write!(f, ", {} per paycheck over {left} paychecks", c.format(per))
+            }
+            (Some(left), None, Some(0)) => {
+                write!(f, ", {} short, no paychecks left", c.format(left))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use crate::error::{Error, Result};
+use crate::locale::Notation;
+use crate::money::Money;
+use crate::util::*;
+
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Month, Weekday};
+
+//how many days ahead due dates are listed at most, about ten years
+pub const MAX_DUE_DAYS: i64 = 3660;
+
+//Recurrence: when an expense comes due
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Recurrence {
+    Once {
+        #[serde(with = "date_format")]
+        date: Date,
+    },
+    //every `interval` weeks, counting from the anchor date
+    Weekly {
+        #[serde(with = "date_format")]
+        anchor: Date,
+        interval: u8,
+    },
+    //days past the end of a month fall on its last day
+    Monthly {
+        day: u8,
+    },
+    Yearly {
+        month: u8,
+        day: u8,
+    },
+}
+impl Recurrence {
+    //from_args(): parses a rule such as "monthly 1", "weekly fri", "biweekly 2026-10-23",
+    //"every 3 weeks fri", "yearly 03-15" or "once 2026-11-01"
+    pub fn from_args(args: &[&str]) -> Result<Recurrence> {
+        let invalid = || Error::InvalidRecurrence(args.join(" "));
+        let arg = |i: usize| args.get(i).copied().ok_or_else(invalid);
+
+        let rule = match arg(0)?.to_ascii_lowercase().as_str() {
+            "once" => Recurrence::Once {
+                date: parse_date(arg(1)?)?,
+            },
+            "weekly" => Recurrence::Weekly {
+                anchor: parse_anchor(arg(1)?)?,
+                interval: 1,
+            },
+            "biweekly" => Recurrence::Weekly {
+                anchor: parse_anchor(arg(1)?)?,
+                interval: 2,
+            },
+            "every" => {
+                let interval: u8 = arg(1)?.parse().map_err(|_| invalid())?;
+                if interval == 0 || !arg(2)?.to_ascii_lowercase().starts_with("week") {
+                    return Err(invalid());
+                }
+                Recurrence::Weekly {
+                    anchor: parse_anchor(arg(3)?)?,
+                    interval,
+                }
+            }
+            "monthly" => {
+                let day: u8 = arg(1)?.parse().map_err(|_| invalid())?;
+                if !(1..=31).contains(&day) {
+                    return Err(invalid());
+                }
+                Recurrence::Monthly { day }
+            }
+            "yearly" => {
+                let (month, day) = arg(1)?.split_once('-').ok_or_else(invalid)?;
+                let month: u8 = month.parse().map_err(|_| invalid())?;
+                let day: u8 = day.parse().map_err(|_| invalid())?;
+                //checked against a leap year, so 02-29 is allowed
+                Date::from_calendar_date(2000, Month::try_from(month).map_err(|_| invalid())?, day)
+                    .map_err(|_| invalid())?;
+                Recurrence::Yearly { month, day }
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(rule)
+    }
+
+    //next_due(): the first due date on or after the given date, if there is one
+    pub fn next_due(&self, from: Date) -> Option<Date> {
+        match self {
+            Recurrence::Once { date } => (*date >= from).then_some(*date),
+            Recurrence::Weekly { anchor, interval } => {
+                if from <= *anchor {
+                    return Some(*anchor);
+                }
+                let period = 7 * *interval as i64;
+                let periods = ((from - *anchor).whole_days() + period - 1) / period;
+                anchor.checked_add(Duration::days(periods * period))
+            }
+            Recurrence::Monthly { day } => {
+                let this_month = day_in_month(from.year(), from.month(), *day)?;
+                if this_month >= from {
+                    return Some(this_month);
+                }
+                let next = from.month().next();
+                let year = match next {
+                    Month::January => from.year() + 1,
+                    _ => from.year(),
+                };
+                day_in_month(year, next, *day)
+            }
+            Recurrence::Yearly { month, day } => {
+                let month = Month::try_from(*month).ok()?;
+                let this_year = day_in_month(from.year(), month, *day)?;
+                if this_year >= from {
+                    return Some(this_year);
+                }
+                day_in_month(from.year() + 1, month, *day)
+            }
+        }
+    }
+
+    //due_between(): every due date in [from, to]
+    pub fn due_between(&self, from: Date, to: Date) -> Vec<Date> {
+        let mut dates = Vec::new();
+        let mut cursor = from;
+        while let Some(date) = self.next_due(cursor) {
+            if date > to {
+                break;
+            }
+            dates.push(date);
+            match date.next_day() {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        dates
+    }
+}
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recurrence::Once { date } => write!(f, "once {date}"),
+            Recurrence::Weekly { anchor, interval } => match interval {
+                1 => write!(f, "weekly {anchor} ({})", anchor.weekday()),
+                2 => write!(f, "biweekly {anchor} ({})", anchor.weekday()),
+                n => write!(f, "every {n} weeks {anchor} ({})", anchor.weekday()),
+            },
+            Recurrence::Monthly { day } => write!(f, "monthly {day}"),
+            Recurrence::Yearly { month, day } => write!(f, "yearly {month:02}-{day:02}"),
+        }
+    }
+}
+
+//parse_anchor(): a YYYY-MM-DD date, or a weekday name meaning the next such day from today
+fn parse_anchor(s: &str) -> Result<Date> {
+    if let Ok(date) = parse_date(s) {
+        return Ok(date);
+    }
+
+    let weekday = match s.to_ascii_lowercase().get(0..3) {
+        Some("mon") => Weekday::Monday,
+        Some("tue") => Weekday::Tuesday,
+        Some("wed") => Weekday::Wednesday,
+        Some("thu") => Weekday::Thursday,
+        Some("fri") => Weekday::Friday,
+        Some("sat") => Weekday::Saturday,
+        Some("sun") => Weekday::Sunday,
+        _ => return Err(Error::InvalidRecurrence(s.into())),
+    };
+    let today = now().date();
+    let days_ahead = (weekday.number_days_from_monday() as i64
+        - today.weekday().number_days_from_monday() as i64)
+        .rem_euclid(7);
+    Ok(add_days(today, days_ahead))
+}
+
+//day_in_month(): the given day of a month, or its last day if the month is shorter,
+//or None past the last year a Date can hold
+pub(crate) fn day_in_month(year: i32, month: Month, day: u8) -> Option<Date> {
+    let day = day.min(time::util::days_in_month(month, year));
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+//DueBill: one upcoming due date of an expense, and whether it is already paid for
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DueBill {
+    pub name: String,
+    #[serde(with = "date_format")]
+    pub date: Date,
+    pub expected: Money,
+    pub paid: Money,
+    pub notation: Notation,
+}
+impl DueBill {
+    //covered(): whether what has been paid this period covers the expected amount
+    pub fn covered(&self) -> bool {
+        self.paid >= self.expected
+    }
+}
+impl std::fmt::Display for DueBill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = (self.date - now().date()).whole_days();
+        let when = match days {
+            0 => String::from("today"),
+            1 => String::from("tomorrow"),
+            n => format!("in {n} days"),
+        };
+        write!(
+            f,
+            "{} ({when}) {}: {}/{}",
+            self.date,
+            to_title_case(self.name.clone()),
+            self.notation.format(self.paid),
+            self.notation.format(self.expected)
+        )?;
+        match self.covered() {
+            true => write!(f, " covered"),
+            false => write!(
+                f,
+                " {} left",
+                self.notation
+                    .format(self.expected.saturating_sub(self.paid))
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        parse_date(s).unwrap()
+    }
+
+    fn rule(args: &str) -> Recurrence {
+        Recurrence::from_args(&args.split(' ').collect::<Vec<&str>>()).unwrap()
+    }
+
+    #[test]
+    fn monthly_falls_on_the_last_day_of_short_months() {
+        let dates = rule("monthly 31").due_between(date("2025-01-01"), date("2025-05-01"));
+        let expected = ["2025-01-31", "2025-02-28", "2025-03-31", "2025-04-30"];
+        assert_eq!(dates, expected.map(date));
+        assert_eq!(
+            rule("monthly 30").next_due(date("2024-02-01")),
+            Some(date("2024-02-29"))
+        );
+        assert_eq!(
+            rule("monthly 15").next_due(date("2025-12-16")),
+            Some(date("2026-01-15"))
+        );
+    }
+
+    #[test]
+    fn yearly_leap_days_fall_on_february_28th_otherwise() {
+        let dates = rule("yearly 02-29").due_between(date("2023-01-01"), date("2025-12-31"));
+        let expected = ["2023-02-28", "2024-02-29", "2025-02-28"];
+        assert_eq!(dates, expected.map(date));
+        assert!(Recurrence::from_args(&["yearly", "02-30"]).is_err());
+        assert!(Recurrence::from_args(&["monthly", "32"]).is_err());
+    }
+
+    #[test]
+    fn weekly_counts_from_the_anchor() {
+        let biweekly = rule("biweekly 2025-01-03");
+        assert_eq!(
+            biweekly.next_due(date("2024-12-01")),
+            Some(date("2025-01-03"))
+        );
+        assert_eq!(
+            biweekly.next_due(date("2025-01-04")),
+            Some(date("2025-01-17"))
+        );
+        assert_eq!(
+            biweekly.next_due(date("2025-01-17")),
+            Some(date("2025-01-17"))
+        );
+        let dates =
+            rule("every 3 weeks 2025-01-03").due_between(date("2025-01-01"), date("2025-02-14"));
+        assert_eq!(dates, ["2025-01-03", "2025-01-24", "2025-02-14"].map(date));
+        assert!(Recurrence::from_args(&["every", "0", "weeks", "fri"]).is_err());
+    }
+
+    #[test]
+    fn once_is_due_only_until_its_date() {
+        let once = rule("once 2025-06-01");
+        assert_eq!(once.next_due(date("2025-06-01")), Some(date("2025-06-01")));
+        assert_eq!(once.next_due(date("2025-06-02")), None);
+        assert_eq!(rule("monthly 15").next_due(Date::MAX), None);
+    }
+}
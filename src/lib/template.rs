@@ -0,0 +1,155 @@
+use crate::currency::Currency;
+use crate::error::Result;
+use crate::expense::{ExpenseSettings, IncomeShare, ShareBasis};
+use crate::income::IncomeSource;
+use crate::locale::Locale;
+use crate::money::{Money, Percent};
+
+use serde::{Deserialize, Serialize};
+
+//BUILTIN_TEMPLATES: name and description of every template that ships with the tool
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "50-30-20",
+        "half of the paycheck to needs, 30% to wants, 20% to savings",
+    ),
+    (
+        "zero-based",
+        "common expenses at $0.00, to give every dollar of income a job",
+    ),
+];
+
+//Template: how a new account starts out, its expenses, income and savings but none of its money
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Template {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub currency: Currency,
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub paycheck: Money,
+    #[serde(default)]
+    pub income_sources: Vec<TemplateIncome>,
+    //in the order the account shows them
+    #[serde(default)]
+    pub expenses: Vec<TemplateExpense>,
+    #[serde(default)]
+    pub savings_goals: Vec<TemplateGoal>,
+    #[serde(default)]
+    pub automatic_payments: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TemplateIncome {
+    pub name: String,
+    #[serde(flatten)]
+    pub source: IncomeSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TemplateExpense {
+    pub name: String,
+    pub amount: Money,
+    #[serde(default)]
+    pub settings: ExpenseSettings,
+}
+
+//TemplateGoal: a savings goal without a deadline, as dates do not carry over between accounts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TemplateGoal {
+    pub name: String,
+    #[serde(default)]
+    pub target: Option<Money>,
+    //share of every paycheck set aside for it
+    #[serde(default)]
+    pub share: Option<Percent>,
+}
+
+impl Template {
+    //builtin(): one of the templates that ship with the tool, by name
+    pub fn builtin(name: &str) -> Option<Template> {
+        let (name, description) = BUILTIN_TEMPLATES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))?;
+        let expense = |name: &str, dollars: i64| TemplateExpense {
+            name: name.into(),
+            amount: Money::from_cents(dollars * 100),
+            settings: ExpenseSettings::default(),
+        };
+        //share(): an expense expected to take a percentage of the paycheck, in thousandths
+        let share = |name: &str, thousandths: u32| TemplateExpense {
+            name: name.into(),
+            amount: Money::ZERO,
+            settings: ExpenseSettings {
+                share: Some(IncomeShare {
+                    percent: Percent::from_thousandths(thousandths),
+                    basis: ShareBasis::Paycheck,
+                }),
+                ..ExpenseSettings::default()
+            },
+        };
+        let goal = |name: &str, thousandths: Option<u32>| TemplateGoal {
+            name: name.into(),
+            target: None,
+            share: thousandths.map(Percent::from_thousandths),
+        };
+        let mut template = Template {
+            name: name.to_string(),
+            description: description.to_string(),
+            ..Template::default()
+        };
+        match *name {
+            "50-30-20" => {
+                template.expenses = vec![
+                    //needs, 50%
+                    share("rent", 30_000),
+                    share("utilities", 5_000),
+                    share("groceries", 10_000),
+                    share("transportation", 5_000),
+                    //wants, 30%
+                    share("dining", 7_500),
+                    share("entertainment", 7_500),
+                    share("shopping", 10_000),
+                    share("subscriptions", 5_000),
+                ];
+                //savings, 20%, set aside for an emergency fund first
+                template.savings_goals = vec![goal("emergency", Some(20_000))];
+            }
+            "zero-based" => {
+                template.expenses = [
+                    "rent",
+                    "utilities",
+                    "groceries",
+                    "transportation",
+                    "insurance",
+                    "debt",
+                    "giving",
+                    "fun",
+                ]
+                .into_iter()
+                .map(|name| expense(name, 0))
+                .collect();
+                template.savings_goals = vec![goal("emergency", None)];
+            }
+            _ => return None,
+        }
+        Some(template)
+    }
+
+    //builtin_names(): every built in template with its description, for help text
+    pub fn builtin_names() -> impl Iterator<Item = (&'static str, &'static str)> {
+        BUILTIN_TEMPLATES.iter().copied()
+    }
+
+    //from_json(): reads a template file, as written by json()
+    pub fn from_json(json: &str) -> Result<Template> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
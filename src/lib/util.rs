@@ -1,49 +1,242 @@
+use crate::money::Money;
 use crate::{Error, Result};
+use time::{Date, Duration, OffsetDateTime};
 
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-//format_dollars(): takes an amount of cents and formats it to ${X}+.XX
-#[cfg_attr(feature = "wasm", wasm_bindgen)]
-pub fn format_dollars(cents: i32) -> String {
-    let cents = { cents.to_string() };
-    let dollars = match cents.len() {
-        3.. => cents.split_at(cents.len() - 2),
-        2 => ("0", cents.as_str()),
-        1 => ("0", &format!("0{cents}")[..]),
-        _ => ("0", "00"),
-    };
-    let mut output = String::from("$");
-    output.push_str(dollars.0);
-    output.push('.');
-    output.push_str(dollars.1);
-    output.to_string()
+//format_dollars(): takes an amount of cents and formats it to $X,XXX.XX, or -$X,XXX.XX if negative
+pub fn format_dollars(cents: i64) -> String {
+    Money::from_cents(cents).to_string()
+}
+
+//parse_dollar_string(): takes a string literal and returns an exact integer cent amount if valid,
+//or which character made it invalid if not
+//accepts thousands separators, a leading or trailing $, a sign or (accounting parentheses) for negatives,
+//and rounds anything past two decimal places half away from zero
+pub fn parse_dollar_string(s: &str) -> Result<i64> {
+    parse_amount(s, &["$"], 2, '.', ',')
 }
 
-//TODO: DOLLAR TRAIT FOR STRINGS AND f32???
+//JS numbers only hold whole numbers exactly up to 2^53 - 1, so the wasm exports take and return cents
+//as numbers within that range, rather than the BigInts an i64 would become
+#[cfg(feature = "wasm")]
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+//format_dollars_js(): format_dollars() for JS, failing on anything but a whole number of cents in range
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = format_dollars)]
+pub fn format_dollars_js(cents: f64) -> Result<String> {
+    if cents.fract() != 0.0 || cents.abs() > MAX_SAFE_INTEGER as f64 {
+        return Err(Error::MoneyOverflow);
+    }
+    Ok(format_dollars(cents as i64))
+}
 
-//dollars_to_cents(): takes a decimal amount of dollars and returns it in integer cents
-pub fn dollars_to_cents(dollars: f32) -> i32 {
-    (dollars * 100.0) as i32
+//parse_dollar_string_js(): parse_dollar_string() for JS, failing on amounts a JS number cannot hold exactly
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = parse_dollar_string)]
+pub fn parse_dollar_string_js(s: &str) -> Result<f64> {
+    let cents = parse_dollar_string(s)?;
+    match cents.abs() <= MAX_SAFE_INTEGER {
+        true => Ok(cents as f64),
+        false => Err(Error::MoneyOverflow),
+    }
 }
 
-//parse_dollar_string(): takes a string literal and returns an integer cent amount if valid, or error message if not
-#[cfg_attr(feature = "wasm", wasm_bindgen)]
-pub fn parse_dollar_string(s: &str) -> Result<i32> {
-    if s.is_empty() {
-        return Err(Error::InvalidDollarValue(s.into()));
+//parse_amount(): like parse_dollar_string(), for a currency with any of the given symbols
+//and minor_units decimal places, written with the given decimal mark and digit grouping separator,
+//returning the amount in minor units
+pub fn parse_amount(
+    s: &str,
+    symbols: &[&str],
+    minor_units: u32,
+    decimal: char,
+    separator: char,
+) -> Result<i64> {
+    //positions are 1-based character counts into s
+    let invalid = |position: usize, reason: String| Error::InvalidDollarValue {
+        value: s.into(),
+        position,
+        reason,
+    };
+    let chars: Vec<char> = s.chars().collect();
+    let mut end = chars.len();
+    while end > 0 && chars[end - 1].is_whitespace() {
+        end -= 1;
     }
-    let mut s = s;
-    if s.starts_with('$') {
-        s = &s[1..];
+    let mut i = chars.iter().take_while(|c| c.is_whitespace()).count();
+    if i >= end {
+        return Err(invalid(1, "no amount given".into()));
     }
-    match s.parse::<i32>() {
-        Ok(n) => Ok(n * 100),
-        Err(_) => match s.parse::<f32>() {
-            Ok(m) => Ok(dollars_to_cents(m)),
-            Err(_) => Err(Error::InvalidDollarValue(s.into())),
-        },
+    //symbol_at(): how many characters a currency symbol takes up at the given index, if one is there
+    let symbol_at = |i: usize| {
+        symbols.iter().find_map(|sym| {
+            let len = sym.chars().count();
+            let found: String = chars.get(i..end)?.iter().take(len).collect();
+            found.eq_ignore_ascii_case(sym).then_some(len)
+        })
+    };
+
+    let mut negative = false;
+    let mut sign = false;
+    let mut parens = false;
+    let mut symbol = false;
+    while i < end {
+        match chars[i] {
+            '(' if !parens && !sign => parens = true,
+            '+' | '-' if !sign && !parens => {
+                sign = true;
+                negative = chars[i] == '-';
+            }
+            '(' | '+' | '-' => return Err(invalid(i + 1, format!("unexpected {:?}", chars[i]))),
+            //a symbol can be set apart from the number
+            c if c.is_whitespace() && symbol => {}
+            c => match symbol_at(i) {
+                Some(len) if !symbol => {
+                    symbol = true;
+                    i += len;
+                    continue;
+                }
+                Some(_) => return Err(invalid(i + 1, format!("unexpected {c:?}"))),
+                None => break,
+            },
+        }
+        i += 1;
     }
+
+    let too_large = |position: usize| invalid(position, "amount is too large".into());
+    let mut whole: i64 = 0;
+    let mut whole_digits = 0;
+    //digits since the last thousands separator, if there was one
+    let mut group: Option<usize> = None;
+    let mut point = false;
+    let mut fraction: Vec<i64> = Vec::new();
+    let bad_group = |position: usize| {
+        invalid(
+            position,
+            "thousands separators need three digits after them".into(),
+        )
+    };
+    while i < end {
+        match chars[i] {
+            c @ '0'..='9' => {
+                let digit = c.to_digit(10).expect("matched-ascii-digit") as i64;
+                if point {
+                    fraction.push(digit);
+                } else {
+                    whole = whole
+                        .checked_mul(10)
+                        .and_then(|w| w.checked_add(digit))
+                        .ok_or_else(|| too_large(i + 1))?;
+                    whole_digits += 1;
+                    if let Some(g) = group.as_mut() {
+                        *g += 1;
+                        if *g > 3 {
+                            return Err(bad_group(i + 1));
+                        }
+                    }
+                }
+            }
+            c if c == separator && point => {
+                return Err(invalid(
+                    i + 1,
+                    "thousands separator after the decimal point".into(),
+                ))
+            }
+            c if c == separator => {
+                if whole_digits == 0 {
+                    return Err(invalid(
+                        i + 1,
+                        "thousands separator before any digits".into(),
+                    ));
+                }
+                if group.is_some_and(|g| g != 3) || (group.is_none() && whole_digits > 3) {
+                    return Err(bad_group(i + 1));
+                }
+                group = Some(0);
+            }
+            c if c == decimal && point => {
+                return Err(invalid(i + 1, "more than one decimal point".into()))
+            }
+            c if c == decimal => {
+                if group.is_some_and(|g| g != 3) {
+                    return Err(bad_group(i + 1));
+                }
+                point = true;
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+    if !point && group.is_some_and(|g| g != 3) {
+        return Err(bad_group(i + 1));
+    }
+    if whole_digits == 0 && fraction.is_empty() {
+        return Err(invalid(i + 1, "expected a digit".into()));
+    }
+
+    let mut closed = false;
+    while i < end {
+        match chars[i] {
+            ')' if parens && !closed => closed = true,
+            c if c.is_whitespace() && !symbol => {}
+            c => match symbol_at(i) {
+                Some(len) if !symbol => {
+                    symbol = true;
+                    i += len;
+                    continue;
+                }
+                _ => return Err(invalid(i + 1, format!("unexpected {c:?}"))),
+            },
+        }
+        i += 1;
+    }
+    if parens && !closed {
+        return Err(invalid(end + 1, "missing closing parenthesis".into()));
+    }
+
+    let digit = |n: usize| fraction.get(n).copied().unwrap_or(0);
+    let kept = (0..minor_units as usize).fold(0, |acc, n| acc * 10 + digit(n));
+    let rounding = (digit(minor_units as usize) >= 5) as i64;
+    let minor = 10i64
+        .checked_pow(minor_units)
+        .and_then(|scale| whole.checked_mul(scale))
+        .and_then(|m| m.checked_add(kept + rounding))
+        .ok_or_else(|| too_large(end))?;
+    match negative || parens {
+        true => Ok(-minor),
+        false => Ok(minor),
+    }
+}
+
+//date_format: serde (de)serialization of Dates as YYYY-MM-DD
+pub use date_format_impl::date_format;
+//wrapped, as the generated module glob-imports its parent
+mod date_format_impl {
+    time::serde::format_description!(pub date_format, Date, "[year]-[month]-[day]");
+}
+
+//now(): the current time, in local time if the offset can be determined, otherwise UTC
+pub fn now() -> OffsetDateTime {
+    OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc())
+}
+
+//add_days(): the date some number of days after the given one, stopping at the first or last Date there is
+pub fn add_days(date: Date, days: i64) -> Date {
+    //no two Dates are further apart, which also keeps Duration::days() from overflowing
+    let span = (Date::MAX - Date::MIN).whole_days();
+    date.checked_add(Duration::days(days.clamp(-span, span)))
+        .unwrap_or(match days < 0 {
+            true => Date::MIN,
+            false => Date::MAX,
+        })
+}
+
+//parse_date(): takes a YYYY-MM-DD string and returns the calendar Date
+pub fn parse_date(s: &str) -> Result<Date> {
+    Date::parse(s, time::macros::format_description!("[year]-[month]-[day]"))
+        .map_err(|_| Error::InvalidDate(s.into()))
 }
 
 //to_title_case(): takes a String and returns a new String with the first letter uppercase, and the rest lowercase
@@ -60,3 +253,107 @@ pub fn to_title_case(s: String) -> String {
     }
     out.clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(s: &str) -> Result<i64> {
+        parse_amount(s, &["$", "USD"], 2, '.', ',')
+    }
+
+    //position(): the character a parse error points at
+    fn position(result: Result<i64>) -> usize {
+        match result {
+            Err(Error::InvalidDollarValue { position, .. }) => position,
+            other => panic!("expected a dollar value error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_amount_rounds_half_away_from_zero() {
+        assert_eq!(usd("1.234").unwrap(), 123);
+        assert_eq!(usd("1.235").unwrap(), 124);
+        assert_eq!(usd("0.999").unwrap(), 100);
+        assert_eq!(usd("-1.235").unwrap(), -124);
+        assert_eq!(usd(".5").unwrap(), 50);
+        assert_eq!(usd("5.").unwrap(), 500);
+        assert_eq!(parse_amount("1.5", &["¥"], 0, '.', ',').unwrap(), 2);
+        assert_eq!(parse_amount("1.2345", &["KD"], 3, '.', ',').unwrap(), 1235);
+    }
+
+    #[test]
+    fn parse_amount_signs() {
+        assert_eq!(usd("+5").unwrap(), 500);
+        assert_eq!(usd("-5").unwrap(), -500);
+        assert_eq!(usd("-$5").unwrap(), -500);
+        assert_eq!(usd("$-5").unwrap(), -500);
+        assert_eq!(usd(" -USD 5 ").unwrap(), -500);
+        assert_eq!(position(usd("--5")), 2);
+        assert_eq!(position(usd("+-5")), 2);
+        assert_eq!(position(usd("5-")), 2);
+    }
+
+    #[test]
+    fn parse_amount_parentheses() {
+        assert_eq!(usd("(5)").unwrap(), -500);
+        assert_eq!(usd("($1,234.56)").unwrap(), -123456);
+        assert_eq!(usd("(5 USD)").unwrap(), -500);
+        assert_eq!(position(usd("(5")), 3);
+        assert_eq!(position(usd("5)")), 2);
+        assert_eq!(position(usd("-(5)")), 2);
+        assert_eq!(position(usd("(-5)")), 2);
+        assert_eq!(position(usd("(5))")), 4);
+    }
+
+    #[test]
+    fn parse_amount_separators() {
+        assert_eq!(usd("1,234.56").unwrap(), 123456);
+        assert_eq!(usd("1,234,567").unwrap(), 123456700);
+        assert_eq!(usd("1234.5").unwrap(), 123450);
+        assert_eq!(
+            parse_amount("1.234,56 €", &["€", "EUR"], 2, ',', '.').unwrap(),
+            123456
+        );
+        assert_eq!(
+            parse_amount("CHF 1'234.50", &["CHF"], 2, '.', '\'').unwrap(),
+            123450
+        );
+        assert_eq!(position(usd(",123")), 1);
+        assert_eq!(position(usd("1234,567")), 5);
+        assert_eq!(position(usd("1,2345")), 6);
+        assert_eq!(position(usd("12,34")), 6);
+        assert_eq!(position(usd("1,23.4")), 5);
+        assert_eq!(position(usd("1.23,4")), 5);
+        assert_eq!(position(usd("1.2.3")), 4);
+    }
+
+    #[test]
+    fn parse_amount_error_positions() {
+        assert_eq!(position(usd("")), 1);
+        assert_eq!(position(usd("   ")), 1);
+        assert_eq!(position(usd("$")), 2);
+        assert_eq!(position(usd("abc")), 1);
+        assert_eq!(position(usd("  $12x")), 6);
+        assert_eq!(position(usd("$$5")), 2);
+        assert_eq!(position(usd("5 $$")), 4);
+        //positions count characters, not bytes
+        assert_eq!(position(parse_amount("€€5", &["€"], 2, ',', '.')), 2);
+        assert_eq!(position(usd("99999999999999999999")), 19);
+        assert_eq!(position(usd("92233720368547758.08")), 20);
+    }
+
+    #[test]
+    fn parse_amount_largest() {
+        assert_eq!(usd("92233720368547758.07").unwrap(), i64::MAX);
+        assert_eq!(usd("-92233720368547758.07").unwrap(), -i64::MAX);
+    }
+
+    #[test]
+    fn add_days_saturates() {
+        let day = parse_date("2025-01-31").unwrap();
+        assert_eq!(add_days(day, 1), parse_date("2025-02-01").unwrap());
+        assert_eq!(add_days(day, i64::MAX), Date::MAX);
+        assert_eq!(add_days(day, i64::MIN), Date::MIN);
+    }
+}
@@ -2,8 +2,10 @@ mod cli;
 mod commands;
 mod error;
 mod fileio;
+mod render;
 
 use error::Result;
+use nlbl::locale::Notation;
 
 fn main() -> Result<()> {
     let cfg = cli::init_app()?;
@@ -12,33 +14,116 @@ fn main() -> Result<()> {
 
     let loaded_budget: nlbl::Budget = fileio::handle_account_load(&cfg)?;
 
-    let worked_budget = match cfg.app_settings.interactive_mode {
-        true => cli::run_interactive(loaded_budget.clone())?,
-        false => nlbl::execute_cmds(
+    let executed = match cfg.app_settings.interactive_mode {
+        //outcomes are shown as they happen in interactive mode
+        true => nlbl::Executed {
+            budget: cli::run_interactive(loaded_budget.clone(), cfg.app_settings.force)?,
+            outcomes: Vec::new(),
+        },
+        false => cli::execute_with_confirmation(
             loaded_budget.clone(),
-            cfg.budget_commands,
+            cfg.budget_commands(loaded_budget.notation())?,
             cfg.app_settings.force,
         )?,
     };
+    let worked_budget = &executed.budget;
 
     //output after processing
     if cfg.app_settings.json {
-        todo!("JSON output")
+        println!("{}", executed.json()?);
     } else {
         match cfg.app_settings.verbosity {
             2.. => {
-                println!("Old:\n{loaded_budget}\nNew:\n{worked_budget}")
+                println!(
+                    "Old:\n{}\nNew:\n{}",
+                    render::budget_view(&loaded_budget),
+                    render::budget_view(worked_budget)
+                )
             }
             1 => {
-                println!("{worked_budget}")
+                println!("{}", render::budget_view(worked_budget))
             }
             0 => {}
         }
+
+        //quiet still shows warnings
+        let outcomes = match cfg.app_settings.verbosity {
+            0 => cli::alerts_view(executed.warnings(), worked_budget.notation()),
+            _ => cli::outcomes_view(&executed.outcomes, worked_budget.notation()),
+        };
+        if !outcomes.is_empty() {
+            println!("{outcomes}");
+        }
+
+        if let Some(filters) = &cfg.app_settings.ledger {
+            println!("{}", cli::ledger_view(worked_budget, filters)?);
+        }
+
+        if let Some(filter) = &cfg.app_settings.periods {
+            let filter = filter.first().map(|s| s.as_str());
+            println!("{}", cli::periods_view(worked_budget, filter));
+        }
+
+        if cfg.app_settings.goals {
+            println!("{}", cli::goals_view(worked_budget));
+        }
+
+        if let Some(repair) = cfg.app_settings.check {
+            //what was found is shown from the budget as loaded, before any repair
+            let findings = loaded_budget.check();
+            println!(
+                "{}",
+                cli::check_view(&findings, worked_budget.notation(), repair)
+            );
+        }
+
+        if let Some(tag) = &cfg.app_settings.tags {
+            println!("{}", cli::tags_view(worked_budget, tag.as_deref()));
+        }
+
+        if let Some(days) = cfg.app_settings.due {
+            println!("{}", cli::due_view(worked_budget, days));
+        }
+
+        if let Some(extra) = &cfg.app_settings.debts {
+            let extra = match extra {
+                Some(extra) => worked_budget.notation().parse(extra)?,
+                None => nlbl::money::Money::ZERO,
+            };
+            println!("{}", cli::debts_view(worked_budget, extra));
+        }
+
+        if let Some(days) = cfg.app_settings.forecast {
+            println!("{}", cli::forecast_view(worked_budget, days));
+        }
+
+        if let Some(home) = &cfg.app_settings.consolidate {
+            let home = match home {
+                Some(code) => Notation::new(code.parse()?, worked_budget.locale()),
+                None => worked_budget.notation(),
+            };
+            //show this account as worked, the rest as saved
+            let mut budgets = match cfg.app_settings.mem_only {
+                true => vec![],
+                false => fileio::load_all_accounts()?,
+            };
+            budgets.retain(|b| b.account != worked_budget.account);
+            budgets.insert(0, worked_budget.clone());
+            let rates = fileio::load_exchange_rates()?;
+            //a missing rate should not lose the changes made this run
+            match cli::consolidated_view(&budgets, home, &rates) {
+                Ok(view) => println!("{view}"),
+                Err(e) => println!("{e}"),
+            }
+        }
     }
 
     //save changes
     if !(cfg.app_settings.mem_only || cfg.app_settings.dry_run) {
-        fileio::save_budget_to_account_file(worked_budget)?;
+        if let Some(name) = &cfg.app_settings.save_template {
+            fileio::save_template(&worked_budget.template(name))?;
+        }
+        fileio::save_budget_to_account_file(executed.budget)?;
     }
 
     Ok(())
@@ -0,0 +1,216 @@
+use nlbl::report::{BudgetStatus, ExpenseReport, Report};
+use nlbl::util;
+use nlbl::Budget;
+
+use console::{measure_text_width, pad_str, style, Alignment, Term};
+
+//NEAR_BUDGET_PERCENT: expenses used this much or more, but not over, are highlighted as close to their budget
+const NEAR_BUDGET_PERCENT: i32 = 80;
+//BAR_MIN: the narrowest progress bar worth drawing
+const BAR_MIN: usize = 6;
+//BAR_MAX: the widest a progress bar gets
+const BAR_MAX: usize = 24;
+//COLUMN_GAP: spaces between table columns
+const COLUMN_GAP: &str = "  ";
+
+//budget_view(): the budget as a table when printing to a terminal, plain text otherwise
+pub fn budget_view(bud: &Budget) -> String {
+    let term = Term::stdout();
+    if !term.is_term() {
+        return bud.to_string();
+    }
+    let (_, width) = term.size();
+    render_report(&bud.report(), width as usize)
+}
+
+//render_report(): the report with its expenses in aligned columns and progress bars, fit to the given width
+//colors follow console's own detection, so NO_COLOR and CLICOLOR turn them off
+pub fn render_report(report: &Report, width: usize) -> String {
+    let mut out = vec![report.header(), String::new()];
+    if report.expenses.is_empty() {
+        out.push(String::from("Expenses:"));
+        return out.join("\n") + "\n";
+    }
+
+    let c = report.notation;
+    let rows: Vec<[String; 4]> = report
+        .expenses
+        .iter()
+        .map(|e| {
+            [
+                util::to_title_case(e.name.clone()),
+                c.format(e.paid),
+                c.format(e.expected),
+                c.format(e.remaining),
+            ]
+        })
+        .collect();
+    let titles = ["Expense", "Paid", "Budget", "Left"];
+    let widths: Vec<usize> = (0..titles.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| measure_text_width(&row[i]))
+                .chain([titles[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    //the bar takes whatever is left of the line after the other columns, "100%" and a spare column,
+    //as some terminals wrap lines that reach the last one
+    let used: usize = widths.iter().sum::<usize>() + 4 + COLUMN_GAP.len() * titles.len() + 1;
+    let bar_width = width.saturating_sub(used + COLUMN_GAP.len()).min(BAR_MAX);
+    let bar_width = (bar_width >= BAR_MIN).then_some(bar_width);
+
+    let mut header: Vec<String> = titles
+        .iter()
+        .zip(widths.iter())
+        .enumerate()
+        .map(|(i, (title, w))| pad_str(title, *w, alignment(i), None).into_owned())
+        .collect();
+    if let Some(w) = bar_width {
+        header.push(" ".repeat(w));
+    }
+    header.push(String::from("Used"));
+    out.push(
+        style(header.join(COLUMN_GAP).trim_end().to_string())
+            .bold()
+            .to_string(),
+    );
+
+    for (exp, row) in report.expenses.iter().zip(rows.iter()) {
+        let color = |s: String| match highlight(exp) {
+            Highlight::Over => style(s).red().to_string(),
+            Highlight::Near => style(s).yellow().to_string(),
+            Highlight::Under => style(s).green().to_string(),
+        };
+        let mut cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .enumerate()
+            .map(|(i, (cell, w))| pad_str(cell, *w, alignment(i), None).into_owned())
+            .collect();
+        if exp.status == BudgetStatus::Over {
+            cells[3] = style(cells[3].clone()).red().to_string();
+        }
+        if let Some(w) = bar_width {
+            cells.push(color(progress_bar(exp.percent_used, w)));
+        }
+        let percent = match exp.percent_used {
+            Some(p) => format!("{p}%"),
+            None => String::from("-"),
+        };
+        cells.push(color(format!("{percent:>4}")));
+
+        let mut notes = Vec::new();
+        if let Some(share) = exp.share {
+            notes.push(share.to_string());
+        }
+        if exp.automatic {
+            notes.push(String::from("auto"));
+        }
+        if let Some(date) = exp.next_due {
+            notes.push(format!("due {date}"));
+        }
+        if !notes.is_empty() {
+            cells.push(style(notes.join(", ")).dim().to_string());
+        }
+        out.push(cells.join(COLUMN_GAP));
+    }
+
+    out.push(String::new());
+    out.push(report.totals());
+    out.join("\n") + "\n"
+}
+
+//Highlight: how an expense's row is colored
+enum Highlight {
+    Over,
+    Near,
+    Under,
+}
+
+fn highlight(exp: &ExpenseReport) -> Highlight {
+    match (exp.status, exp.percent_used) {
+        (BudgetStatus::Over, _) => Highlight::Over,
+        (BudgetStatus::Under, Some(p)) if p >= NEAR_BUDGET_PERCENT => Highlight::Near,
+        _ => Highlight::Under,
+    }
+}
+
+//alignment(): names on the left, amounts on the right
+fn alignment(column: usize) -> Alignment {
+    match column {
+        0 => Alignment::Left,
+        _ => Alignment::Right,
+    }
+}
+
+//progress_bar(): how much of an expense is used, full at 100% and beyond
+fn progress_bar(percent: Option<i32>, width: usize) -> String {
+    let filled = percent.unwrap_or(0).clamp(0, 100) as usize * width / 100;
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use console::strip_ansi_codes;
+    use nlbl::force::Force;
+    use nlbl::money::Money;
+
+    //report(): $1,000 paid in, $500 rent and $200 food expected, $50 paid to food
+    fn report() -> Report {
+        let mut bud = Budget::new("test");
+        bud.get_paid_value(Money::from_cents(100_000), None)
+            .unwrap();
+        bud.add_expense("rent", Money::from_cents(50_000));
+        bud.add_expense("food", Money::from_cents(20_000));
+        bud.make_dynamic_payment("food", Money::from_cents(5_000), None, Force::NoConfirm)
+            .unwrap();
+        bud.report()
+    }
+
+    //table(): the expense rows of a rendered report, without colors
+    fn table(width: usize) -> Vec<String> {
+        strip_ansi_codes(&render_report(&report(), width))
+            .lines()
+            .skip_while(|line| !line.starts_with("Expense"))
+            .take_while(|line| !line.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn bar_fits_the_width() {
+        for width in [48, 60, 80, 200] {
+            let rows = table(width);
+            assert_eq!(rows.len(), 3);
+            for row in rows.iter() {
+                assert!(measure_text_width(row) < width, "{width}: {row}");
+            }
+            let bar = rows[2].chars().filter(|c| *c == '█' || *c == '░').count();
+            assert!((BAR_MIN..=BAR_MAX).contains(&bar));
+        }
+        //25% of a 24 column bar
+        assert!(table(200)[2].contains(&format!("{}{}", "█".repeat(6), "░".repeat(18))));
+    }
+
+    #[test]
+    fn narrow_widths_drop_the_bar_but_keep_the_columns() {
+        for width in [0, 20, 47] {
+            let rows = table(width);
+            assert!(rows.iter().all(|row| !row.contains('░')));
+            assert_eq!(rows[0], "Expense    Paid   Budget     Left  Used");
+            assert_eq!(rows[2], "Food     $50.00  $200.00  $150.00   25%");
+        }
+    }
+
+    #[test]
+    fn progress_bar_is_full_at_100_percent() {
+        assert_eq!(progress_bar(Some(50), 6), "███░░░");
+        assert_eq!(progress_bar(Some(150), 6), "██████");
+        assert_eq!(progress_bar(Some(-10), 6), "░░░░░░");
+        assert_eq!(progress_bar(None, 6), "░░░░░░");
+    }
+}